@@ -0,0 +1,9 @@
+fn main() {
+    // The libarchive backend calls libarchive's C API itself, so the library
+    // is found here, where pkg-config knows it is installed.
+    #[cfg(feature = "libarchive")]
+    pkg_config::Config::new()
+        .atleast_version("3")
+        .probe("libarchive")
+        .expect("libarchive was not found, build with --no-default-features to leave it out");
+}
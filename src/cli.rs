@@ -1,13 +1,90 @@
+use crate::error::Error;
+use crate::export::{Columns, OutputFormat};
+use crate::library::LibraryGrouping;
+use crate::osu::OsuMode;
+use std::collections::BTreeMap as Map;
+use std::io::Read;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, new)]
 pub struct CliArguments {
     pub osu_source: PathBuf,
-    pub packs_source: PathBuf,
+    pub packs_sources: Vec<PathBuf>,
+    // The paths read from stdin, under the packs source standing for them.
+    #[new(default)]
+    pub listed_paths: Map<PathBuf, Vec<PathBuf>>,
+    #[new(value = "std::thread::available_parallelism().map(|x| x.get()).unwrap_or(1)")]
+    pub threads: usize,
+    #[new(default)]
+    pub no_cache: bool,
+    #[new(default)]
+    pub rebuild_cache: bool,
+    #[new(default)]
+    pub cache_file: Option<PathBuf>,
+    #[new(default)]
+    pub passwords: Option<PathBuf>,
+    #[new(default)]
+    pub library: Option<LibraryGrouping>,
+    #[new(default)]
+    pub includes: Vec<String>,
+    #[new(default)]
+    pub excludes: Vec<String>,
+    #[new(default)]
+    pub id_patterns: Vec<String>,
+    #[new(default)]
+    pub no_default_id_patterns: bool,
+    #[new(value = "100000")]
+    pub max_entries: usize,
+    #[new(value = "65536")]
+    pub max_size: u64,
+    #[new(value = "16")]
+    pub max_entry_depth: usize,
+    #[new(value = "300")]
+    pub archive_timeout: u64,
+    #[new(default)]
+    pub catalogue: Option<PathBuf>,
+    #[new(value = "OutputFormat::Text")]
+    pub format: OutputFormat,
+    #[new(default)]
+    pub columns: Option<Columns>,
+    #[new(default)]
+    pub per_difficulty: bool,
+    #[new(default)]
+    pub thumbnails: bool,
+    #[new(default)]
+    pub duplicates: bool,
+    #[new(default)]
+    pub verify: bool,
+    #[new(default)]
+    pub count_once: bool,
+    #[new(default)]
+    pub install: bool,
+    #[new(default)]
+    pub install_to: Option<PathBuf>,
+    #[new(default)]
+    pub stage: Option<PathBuf>,
+    #[new(default)]
+    pub hard_link: bool,
+    #[new(default)]
+    pub packs: Vec<String>,
+    #[new(default)]
+    pub modes: Vec<OsuMode>,
+    #[new(default)]
+    pub min_stars: Option<f64>,
+    #[new(default)]
+    pub max_stars: Option<f64>,
+    #[new(default)]
+    pub dry_run: bool,
+    #[new(default)]
+    pub limit: Option<usize>,
+    #[new(default)]
+    pub watch: bool,
+    #[new(value = "1500")]
+    pub watch_debounce: u64,
 }
 
 pub fn get_arguments_parsed() -> CliArguments {
-    let mut ca = CliArguments::new(PathBuf::from(""), PathBuf::from(""));
+    let mut ca = CliArguments::new(PathBuf::from(""), vec![]);
     {
         let mut parser = argparse::ArgumentParser::new();
         parser.set_description("Checks which beatmap sets you haven't played yet.");
@@ -17,10 +94,244 @@ pub fn get_arguments_parsed() -> CliArguments {
             .add_argument("osu_source", argparse::Store, "Your Osu! folder")
             .required();
         parser
-            .refer(&mut ca.packs_source)
-            .add_argument("packs_source", argparse::Store, "Beatmapsets folder (folder to '.osz's, which can be inside .zip, .7z or .rar archives, but not nested)")
-            .required();
+            .refer(&mut ca.packs_sources)
+            .add_argument("packs_source", argparse::List, "Beatmapsets folders, any number of them (folders to '.osz's, which can be inside .zip, .7z, .rar or .tar (also .tar.gz, .tar.zst and .tar.xz) archives, but not nested, or to .txt, .csv and .tsv lists of beatmapset ids or URLs, which inside folders are only read when every line is one or there is an id column), - reads newline or NUL separated paths from stdin");
+        parser.refer(&mut ca.threads).add_option(
+            &["-j", "--threads"],
+            argparse::Store,
+            "How many archives are listed at the same time (defaults to the number of CPUs)",
+        );
+        parser.refer(&mut ca.no_cache).add_option(
+            &["--no-cache"],
+            argparse::StoreTrue,
+            "Lists every archive again without reading or writing the archive cache",
+        );
+        parser.refer(&mut ca.rebuild_cache).add_option(
+            &["--rebuild-cache"],
+            argparse::StoreTrue,
+            "Forgets every cached archive listing before scanning",
+        );
+        parser.refer(&mut ca.cache_file).add_option(
+            &["--cache-file"],
+            argparse::StoreOption,
+            "Where archive listings are cached (defaults to ~/.cache/osu-player-trainer/archives.sqlite3)",
+        );
+        parser.refer(&mut ca.passwords).add_option(
+            &["--passwords"],
+            argparse::StoreOption,
+            "File of encrypted archive passwords, one archive name and its password per line, separated by a tab (volume sets go by their archive name, e.g. pack.rar for pack.part1.rar)",
+        );
+        parser.refer(&mut ca.library).add_option(
+            &["--library"],
+            argparse::StoreOption,
+            "Also lists the beatmapsets installed in osu!stable, grouped by artist, creator or letter (first letter of the title)",
+        );
+        parser.refer(&mut ca.includes).add_option(
+            &["--include"],
+            argparse::Collect,
+            "Only keeps '.osz's whose path inside their packs_source matches this glob (e.g. \"S*/**\"), can be repeated",
+        );
+        parser.refer(&mut ca.excludes).add_option(
+            &["--exclude"],
+            argparse::Collect,
+            "Skips files, folders and archives whose path inside their packs_source matches this glob (e.g. \"**/old\"), can be repeated",
+        );
+        parser.refer(&mut ca.id_patterns).add_option(
+            &["--id-pattern"],
+            argparse::Collect,
+            "Regex with a capture group named \"id\" finding the beatmapset id in '.osz' names, tried in order before the defaults, can be repeated",
+        );
+        parser.refer(&mut ca.no_default_id_patterns).add_option(
+            &["--no-default-id-patterns"],
+            argparse::StoreTrue,
+            "Only tries the --id-pattern's, not the defaults (tried in this order: \"(123456)\" of three digits or more at the end, \"beatmapset-123456\" and leading digits)",
+        );
+        parser.refer(&mut ca.max_entries).add_option(
+            &["--max-entries"],
+            argparse::Store,
+            "Stops reading archives once more entries than this have been found in them (defaults to 100000)",
+        );
+        parser.refer(&mut ca.max_size).add_option(
+            &["--max-size"],
+            argparse::Store,
+            "Stops reading archives once more MiB than this have been decompressed from them (defaults to 65536)",
+        );
+        parser.refer(&mut ca.max_entry_depth).add_option(
+            &["--max-entry-depth"],
+            argparse::Store,
+            "Skips archive entries whose path inside their archive has more directories than this (defaults to 16)",
+        );
+        parser.refer(&mut ca.archive_timeout).add_option(
+            &["--archive-timeout"],
+            argparse::Store,
+            "Gives up on archives taking more seconds than this to list or extract from, 0 to wait forever (defaults to 300)",
+        );
+        parser.refer(&mut ca.catalogue).add_option(
+            &["--catalogue"],
+            argparse::StoreOption,
+            "Beatmap pack catalogue (.json or .csv) used to report beatmapsets and packs missing from your packs",
+        );
+        parser.refer(&mut ca.format).add_option(
+            &["--format"],
+            argparse::Store,
+            "How the report is printed: text (the default), json (its schema is described in README.md), csv and tsv with one row per '.osz', html (a standalone page to redirect into a file) or markdown (headings and tables to paste into a wiki)",
+        );
+        parser.refer(&mut ca.columns).add_option(
+            &["--columns"],
+            argparse::StoreOption,
+            "Comma separated csv and tsv columns among path, pack, beatmapset_id, status, grade, played, difficulties, min_stars, max_stars, last_played, beatmap_id, difficulty, mode and stars",
+        );
+        parser.refer(&mut ca.per_difficulty).add_option(
+            &["--per-difficulty"],
+            argparse::StoreTrue,
+            "Writes one csv or tsv row per difficulty instead of one per '.osz'",
+        );
+        parser.refer(&mut ca.thumbnails).add_option(
+            &["--thumbnails"],
+            argparse::StoreTrue,
+            "Shows the beatmapset backgrounds in the html report, which reads every '.osz' that is not installed and is slow for big collections",
+        );
+        parser.refer(&mut ca.duplicates).add_option(
+            &["--duplicates"],
+            argparse::StoreTrue,
+            "Lists every beatmapset found at more than one path, with sizes and whether their contents differ",
+        );
+        parser.refer(&mut ca.verify).add_option(
+            &["--verify"],
+            argparse::StoreTrue,
+            "Checks the CRC32 of every '.osz', including those inside archives, and lists the corrupt or truncated ones",
+        );
+        parser.refer(&mut ca.count_once).add_option(
+            &["--count-once"],
+            argparse::StoreTrue,
+            "Counts each beatmapset only once in the summary and in the folder statistics of the json, html and markdown reports, however many packs contain it",
+        );
+        parser.refer(&mut ca.install).add_option(
+            &["--install"],
+            argparse::StoreTrue,
+            "Copies the '.osz' of every beatmapset that is not installed yet into osu! instead of printing the report",
+        );
+        parser.refer(&mut ca.install_to).add_option(
+            &["--install-to"],
+            argparse::StoreOption,
+            "Where --install puts the '.osz's (defaults to the Songs folder of osu!stable, give lazer's import folder here)",
+        );
+        parser.refer(&mut ca.stage).add_option(
+            &["--stage"],
+            argparse::StoreOption,
+            "Copies the '.osz' of every beatmapset not played yet into this folder, keeping the packs' directories (an archive becomes a folder named like it without its suffix), instead of printing the report",
+        );
+        parser.refer(&mut ca.hard_link).add_option(
+            &["--hard-link"],
+            argparse::StoreTrue,
+            "Makes --stage hard-link loose '.osz's instead of copying them",
+        );
+        parser.refer(&mut ca.packs).add_option(
+            &["--pack"],
+            argparse::Collect,
+            "Only stages beatmapsets of this pack (e.g. S140) or pack series (e.g. T), can be repeated",
+        );
+        parser.refer(&mut ca.modes).add_option(
+            &["--mode"],
+            argparse::Collect,
+            "Only stages beatmapsets with difficulties of this mode (osu, taiko, catch or mania), can be repeated",
+        );
+        parser.refer(&mut ca.min_stars).add_option(
+            &["--min-stars"],
+            argparse::StoreOption,
+            "Only stages beatmapsets with a difficulty of at least this many stars (only known for installed beatmapsets)",
+        );
+        parser.refer(&mut ca.max_stars).add_option(
+            &["--max-stars"],
+            argparse::StoreOption,
+            "Only stages beatmapsets with a difficulty of at most this many stars (only known for installed beatmapsets)",
+        );
+        parser.refer(&mut ca.dry_run).add_option(
+            &["--dry-run"],
+            argparse::StoreTrue,
+            "Only prints what --install or --stage would copy",
+        );
+        parser.refer(&mut ca.limit).add_option(
+            &["--limit"],
+            argparse::StoreOption,
+            "Installs or stages at most this many '.osz's",
+        );
+        parser.refer(&mut ca.watch).add_option(
+            &["-w", "--watch"],
+            argparse::StoreTrue,
+            "Keeps running, printing the report again whenever osu! or the packs change",
+        );
+        parser.refer(&mut ca.watch_debounce).add_option(
+            &["--watch-debounce"],
+            argparse::Store,
+            "How many milliseconds to wait for changes to settle before printing again (defaults to 1500)",
+        );
         parser.parse_args_or_exit();
     }
     ca
 }
+
+// Replaces "-" by the deepest folder holding every path read from stdin, so
+// that they are scanned together as one packs source.
+pub fn expand_stdin_sources(args: &mut CliArguments) -> Result<(), Error> {
+    if !args
+        .packs_sources
+        .iter()
+        .any(|source| source.as_os_str() == "-")
+    {
+        return Ok(());
+    }
+    let stdin_error = |err| Error::Io(PathBuf::from("-"), err);
+    let mut input = vec![];
+    std::io::stdin()
+        .read_to_end(&mut input)
+        .map_err(stdin_error)?;
+    let current_dir = std::env::current_dir().map_err(stdin_error)?;
+    let separator = if input.contains(&b'\0') { b'\0' } else { b'\n' };
+    let paths: Vec<PathBuf> = input
+        .split(|byte| *byte == separator)
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+        .filter(|line| !line.is_empty())
+        .map(|line| current_dir.join(path_from_bytes(line)))
+        .collect();
+    let root = common_ancestor(&paths);
+    let mut expanded = vec![];
+    for source in args.packs_sources.drain(..) {
+        if source.as_os_str() != "-" {
+            expanded.push(source);
+        } else if let Some(root) = root.as_ref().filter(|root| !expanded.contains(*root)) {
+            expanded.push(root.clone());
+        }
+    }
+    args.packs_sources = expanded;
+    if let Some(root) = root {
+        args.listed_paths.insert(root, paths);
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+fn common_ancestor(paths: &[PathBuf]) -> Option<PathBuf> {
+    let mut ancestor = paths.first()?.clone();
+    for path in paths {
+        while !path.starts_with(&ancestor) {
+            if !ancestor.pop() {
+                break;
+            }
+        }
+    }
+    if ancestor.is_file() {
+        ancestor.pop();
+    }
+    Some(ancestor)
+}
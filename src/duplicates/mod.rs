@@ -0,0 +1,65 @@
+use crate::error::Error;
+use crate::osz_finder::{crc32, Osz, OszFinder};
+use std::collections::BTreeMap as Map;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, new)]
+pub struct Duplicate {
+    pub beatmapset_id: u64,
+    pub oszs: Vec<Osz>,
+}
+
+impl Duplicate {
+    pub fn contents_differ(&self) -> Option<bool> {
+        let mut sizes: Vec<u64> = self.oszs.iter().filter_map(|osz| osz.size).collect();
+        sizes.sort();
+        sizes.dedup();
+        if sizes.len() > 1 {
+            return Some(true);
+        }
+        let mut crcs = self
+            .oszs
+            .iter()
+            .map(|osz| osz.crc32)
+            .collect::<Option<Vec<u32>>>()?;
+        crcs.sort();
+        crcs.dedup();
+        Some(crcs.len() > 1)
+    }
+}
+
+pub fn find_duplicates(finder: &OszFinder, oszs: &[Osz]) -> Vec<Duplicate> {
+    let mut by_id: Map<u64, Vec<Osz>> = Map::new();
+    for osz in oszs.iter().filter(|osz| osz.list.is_none()) {
+        by_id
+            .entry(osz.beatmapset_id)
+            .or_default()
+            .push(osz.clone());
+    }
+    by_id.retain(|_, oszs| oszs.len() > 1);
+    let unhashed: Vec<Osz> = by_id
+        .values()
+        .flatten()
+        .filter(|osz| osz.crc32.is_none())
+        .cloned()
+        .collect();
+    let hashes: Map<PathBuf, (u64, u32)> = finder
+        .map_contents(&unhashed, |osz, reader| {
+            crc32(reader).map_err(|err| Error::Io(osz.path.clone(), err))
+        })
+        .into_iter()
+        .filter_map(|(osz, hash)| Some((osz.path, hash.ok()?)))
+        .collect();
+    by_id
+        .into_iter()
+        .map(|(beatmapset_id, mut oszs)| {
+            for osz in oszs.iter_mut() {
+                if let Some((size, crc32)) = hashes.get(&osz.path) {
+                    osz.size = Some(*size);
+                    osz.crc32 = Some(*crc32);
+                }
+            }
+            Duplicate::new(beatmapset_id, oszs)
+        })
+        .collect()
+}
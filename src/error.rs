@@ -0,0 +1,109 @@
+use std::path::PathBuf;
+
+#[derive(Debug, Display)]
+pub enum Error {
+    #[display(fmt = "{:?} is neither a directory nor a file", _0)]
+    NotFound(PathBuf),
+    #[display(fmt = "{:?} is not a directory", _0)]
+    NotADirectory(PathBuf),
+    #[display(fmt = "{:?} directory was not found in your {} directory", _0, _1)]
+    MissingDirectory(PathBuf, &'static str),
+    #[display(fmt = "{:?} file was not found in your {} directory", _0, _1)]
+    MissingFile(PathBuf, &'static str),
+    #[display(fmt = "--library can only list the beatmapsets of osu!stable, not osu!lazer")]
+    UnsupportedLibrary,
+    #[display(fmt = "{:?} file was deemed unreadable because {}", _0, _1)]
+    UnreadableDatabase(PathBuf, String),
+    #[display(fmt = "{:?} could not be read because {}", _0, _1)]
+    Io(PathBuf, std::io::Error),
+    #[display(fmt = "{:?} is not a valid UTF-8 path", _0)]
+    NonUtf8Path(PathBuf),
+    #[display(fmt = "{:?} has no beatmapset id in its name", _0)]
+    NoBeatmapsetId(PathBuf),
+    #[display(fmt = "{:?} is not a valid id pattern because {}", _0, _1)]
+    InvalidIdPattern(String, String),
+    #[display(fmt = "{:?} is not a valid glob pattern because {}", _0, _1)]
+    InvalidGlob(String, String),
+    #[display(fmt = "{:?} could not be listed as an archive because {}", _0, _1)]
+    UnlistableArchive(PathBuf, String),
+    #[display(fmt = "{:?} is missing its volume {:?}", _0, _1)]
+    MissingVolume(PathBuf, PathBuf),
+    #[display(fmt = "{:?} has no entry named {:?}", _0, _1)]
+    MissingEntry(PathBuf, String),
+    #[display(fmt = "{:?} could not be extracted because {}", _0, _1)]
+    Unextractable(PathBuf, String),
+    #[display(fmt = "{:?} has more than {} entries", _0, _1)]
+    TooManyEntries(PathBuf, usize),
+    #[display(fmt = "{:?} decompresses to more than {} bytes", _0, _1)]
+    TooLarge(PathBuf, u64),
+    #[display(
+        fmt = "{:?} entry {:?} is nested deeper than {} directories",
+        _0,
+        _1,
+        _2
+    )]
+    TooDeep(PathBuf, String, usize),
+    #[display(fmt = "{:?} entry {:?} points outside of the archive", _0, _1)]
+    UnsafeEntryName(PathBuf, String),
+    #[display(fmt = "{:?} took longer than {} seconds to read", _0, _1)]
+    ArchiveTimeout(PathBuf, u64),
+    #[display(fmt = "{:?} is truncated ({} bytes instead of {})", _0, _2, _1)]
+    TruncatedOsz(PathBuf, u64, u64),
+    #[display(
+        fmt = "{:?} does not match its CRC32 ({:08x} instead of {:08x})",
+        _0,
+        _2,
+        _1
+    )]
+    ChecksumMismatch(PathBuf, u32, u32),
+    #[display(fmt = "{:?} is corrupt because {}", _0, _1)]
+    CorruptOsz(PathBuf, String),
+    #[display(fmt = "{:?} is encrypted and no password is known for it", _0)]
+    EncryptedArchive(PathBuf),
+    #[display(fmt = "{:?} could not be decrypted with its password", _0)]
+    WrongPassword(PathBuf),
+    #[display(
+        fmt = "{:?} line {} is not an archive name and a password separated by a tab",
+        _0,
+        _1
+    )]
+    MalformedPasswordFile(PathBuf, usize),
+    #[display(fmt = "{:?} archive cache could not be used because {}", _0, _1)]
+    Cache(PathBuf, String),
+    #[display(fmt = "{:?} could not be watched because {}", _0, _1)]
+    Watch(PathBuf, String),
+    #[display(fmt = "{:?} catalogue could not be read because {}", _0, _1)]
+    Catalogue(PathBuf, String),
+    #[display(fmt = "{:?} beatmapset list could not be read because {}", _0, _1)]
+    IdList(PathBuf, String),
+    #[display(
+        fmt = "{:?} line {} is neither a beatmapset id nor a beatmapset link",
+        _0,
+        _1
+    )]
+    MalformedIdList(PathBuf, usize),
+    #[display(fmt = "{} '.osz's could not be installed", _0)]
+    InstallFailed(usize),
+    #[display(fmt = "{} '.osz's could not be staged", _0)]
+    StageFailed(usize),
+    #[display(fmt = "the report could not be written because {}", _0)]
+    Export(String),
+    #[display(fmt = "{} only applies to --format csv and tsv", _0)]
+    TableOnlyOption(&'static str),
+    #[display(fmt = "neither a packs_source nor --library was given")]
+    NoSources,
+    #[display(fmt = "no beatmapset was found in {:?}", _0)]
+    NoBeatmapsets(Vec<PathBuf>),
+    #[display(fmt = "no osu! installation could be opened: {}", "join(_0)")]
+    NoOsuInstallation(Vec<Error>),
+}
+
+impl std::error::Error for Error {}
+
+fn join(errors: &[Error]) -> String {
+    errors
+        .iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
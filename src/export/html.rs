@@ -0,0 +1,172 @@
+use super::{status_key, status_label};
+use crate::error::Error;
+use crate::osu::OsuBeatmapStatus;
+use crate::osz_finder::Osz;
+use crate::pathtree_stylizer::PathTreeStylized;
+use crate::statistics::Statistics;
+use std::collections::BTreeMap as Map;
+use std::fmt::Write;
+use std::path::Path;
+
+const STYLE: &str = "
+body { font-family: sans-serif; background: #1e1e1e; color: #d4d4d4; margin: 2em; }
+a { color: inherit; }
+ul.tree, ul.tree ul { list-style: none; padding-left: 1.5em; }
+ul.tree { padding-left: 0; }
+summary { cursor: pointer; }
+.segment { opacity: 0.7; }
+.status { font-weight: bold; }
+.status.played { color: #4ec94e; }
+.status.not_played { color: #f14c4c; }
+.status.not_installed { color: #f14c4c; background: #e5e510; padding: 0 0.2em; }
+.percentage { opacity: 0.7; font-size: 0.9em; }
+.thumbnail { display: inline-block; width: 80px; height: 45px; object-fit: cover; vertical-align: middle; margin: 0.1em 0.5em 0.1em 0; border-radius: 3px; background: #333; }
+.hidden { display: none; }
+#search { width: 100%; max-width: 40em; padding: 0.4em; margin-bottom: 1em; }
+";
+
+const SCRIPT: &str = "
+document.getElementById('search').addEventListener('input', function (event) {
+  var query = event.target.value.toLowerCase();
+  var sets = document.querySelectorAll('li.set');
+  sets.forEach(function (set) {
+    set.classList.toggle('hidden', query !== '' && set.dataset.search.indexOf(query) < 0);
+  });
+  document.querySelectorAll('li.dir').forEach(function (dir) {
+    var shown = dir.querySelectorAll('li.set:not(.hidden)').length > 0;
+    dir.classList.toggle('hidden', !shown);
+    if (query !== '') {
+      dir.querySelector('details').open = shown;
+    }
+  });
+});
+";
+
+#[derive(new)]
+pub struct HtmlReport<'a> {
+    tree: &'a PathTreeStylized<OsuBeatmapStatus>,
+    statuses: &'a [(&'a Osz, &'a OsuBeatmapStatus)],
+    summary: &'a Statistics,
+    count_once: bool,
+    errors: &'a [Error],
+    thumbnails: &'a Map<u64, String>,
+}
+
+impl HtmlReport<'_> {
+    pub fn render(&self) -> String {
+        let oszs: Map<&Path, (&Osz, &OsuBeatmapStatus)> = self
+            .statuses
+            .iter()
+            .map(|(osz, status)| (osz.path.as_path(), (*osz, *status)))
+            .collect();
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{} report</title>\n<style>{}</style>\n</head>\n<body>\n",
+            env!("CARGO_PKG_NAME"),
+            STYLE
+        );
+        let _ = writeln!(
+            html,
+            "<h1>Beatmapsets played</h1>\n<p>Summary: {}</p>",
+            escape(&self.summary.to_string())
+        );
+        html.push_str("<input id=\"search\" type=\"search\" placeholder=\"Search beatmapsets\">\n");
+        html.push_str("<ul class=\"tree\">\n");
+        for child in self.tree.children() {
+            html.push_str(&render_node(child, Path::new(""), &oszs, self).0);
+        }
+        html.push_str("</ul>\n");
+        if !self.errors.is_empty() {
+            let _ = writeln!(
+                html,
+                "<h2>Problems found while scanning ({})</h2>\n<ul>",
+                self.errors.len()
+            );
+            for err in self.errors {
+                let _ = writeln!(html, "<li>{}</li>", escape(&err.to_string()));
+            }
+            html.push_str("</ul>\n");
+        }
+        let _ = write!(html, "<script>{}</script>\n</body>\n</html>\n", SCRIPT);
+        html
+    }
+}
+
+fn render_node(
+    tree: &PathTreeStylized<OsuBeatmapStatus>,
+    parent: &Path,
+    oszs: &Map<&Path, (&Osz, &OsuBeatmapStatus)>,
+    report: &HtmlReport,
+) -> (String, Vec<(u64, OsuBeatmapStatus)>) {
+    let path = parent.join(tree.segment());
+    let status = tree.data().unwrap_or(&OsuBeatmapStatus::NotInstalled);
+    let mut html = String::new();
+    if let Some((osz, status)) = oszs.get(path.as_path()) {
+        let _ = writeln!(
+            html,
+            "<li class=\"set\" data-search=\"{}\">{}<a href=\"https://osu.ppy.sh/beatmapsets/{}\">{}</a> {}</li>",
+            escape(&path.display().to_string().to_lowercase()),
+            thumbnail(report.thumbnails, osz.beatmapset_id),
+            osz.beatmapset_id,
+            escape(tree.segment()),
+            status_span(status)
+        );
+        return (html, vec![(osz.beatmapset_id, **status)]);
+    }
+    let mut children = String::new();
+    let mut statuses = vec![];
+    for child in tree.children() {
+        let (child_html, child_statuses) = render_node(child, &path, oszs, report);
+        children.push_str(&child_html);
+        statuses.extend(child_statuses);
+    }
+    let statistics = Statistics::from_statuses(
+        statuses
+            .iter()
+            .map(|(beatmapset_id, status)| (*beatmapset_id, status)),
+        report.count_once,
+    );
+    let _ = writeln!(
+        html,
+        "<li class=\"dir\"><details open><summary><span class=\"segment\">{}</span> {} <span class=\"percentage\">{}/{} ({:.1}%)</span></summary>\n<ul>\n{}</ul></details></li>",
+        escape(tree.segment()),
+        status_span(status),
+        statistics.played,
+        statistics.total(),
+        statistics.percentage(),
+        children
+    );
+    (html, statuses)
+}
+
+fn thumbnail(thumbnails: &Map<u64, String>, beatmapset_id: u64) -> String {
+    match thumbnails.get(&beatmapset_id) {
+        Some(uri) => format!("<img class=\"thumbnail\" src=\"{}\" alt=\"\">", uri),
+        None if thumbnails.is_empty() => String::new(),
+        None => "<span class=\"thumbnail\"></span>".to_string(),
+    }
+}
+
+fn status_span(status: &OsuBeatmapStatus) -> String {
+    format!(
+        "<span class=\"status {}\">{}</span>",
+        status_key(status),
+        status_label(status)
+    )
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,211 @@
+use super::{grade_key, mode_key, status_key};
+use crate::error::Error;
+use crate::osu::{Osu, OsuBeatmap, OsuBeatmapStatus, OsuMode};
+use crate::osz_finder::Osz;
+use crate::pathtree_stylizer::PathTreeStylized;
+use crate::statistics::Statistics;
+use serde::Serialize;
+use std::collections::BTreeMap as Map;
+use std::path::Path;
+
+// The `--format json` schema and when its version changes are described in README.md.
+pub const SCHEMA_VERSION: u32 = 1;
+
+const MODES: [OsuMode; 4] = [
+    OsuMode::Standard,
+    OsuMode::Taiko,
+    OsuMode::Catch,
+    OsuMode::Mania,
+];
+
+#[derive(Debug, Serialize)]
+pub struct JsonReport {
+    schema_version: u32,
+    generator: String,
+    summary: JsonStatistics,
+    sources: Vec<JsonNode>,
+    unmatched: Vec<String>,
+    problems: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonNode {
+    segment: String,
+    path: String,
+    #[serde(flatten)]
+    status: JsonStatus,
+    statistics: JsonStatistics,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    beatmapset: Option<JsonBeatmapset>,
+    children: Vec<JsonNode>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonStatus {
+    status: &'static str,
+    grade: Option<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonStatistics {
+    total: usize,
+    played: usize,
+    not_played: usize,
+    not_installed: usize,
+    percentage: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonBeatmapset {
+    id: u64,
+    archive: Option<String>,
+    entry: Option<String>,
+    list: Option<String>,
+    size: Option<u64>,
+    crc32: Option<u32>,
+    difficulties: Vec<JsonDifficulty>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonDifficulty {
+    id: u64,
+    mode: Option<&'static str>,
+    stars: Option<f64>,
+    grades: Map<&'static str, JsonStatus>,
+}
+
+impl JsonReport {
+    pub fn new(
+        osu: &dyn Osu,
+        tree: &PathTreeStylized<OsuBeatmapStatus>,
+        statuses: &[(&Osz, &OsuBeatmapStatus)],
+        summary: &Statistics,
+        count_once: bool,
+        errors: &[Error],
+    ) -> Self {
+        let oszs: Map<&Path, (&Osz, &OsuBeatmapStatus)> = statuses
+            .iter()
+            .map(|(osz, status)| (osz.path.as_path(), (*osz, *status)))
+            .collect();
+        let (unmatched, problems): (Vec<&Error>, Vec<&Error>) = errors
+            .iter()
+            .partition(|err| matches!(err, Error::NoBeatmapsetId(_)));
+        Self {
+            schema_version: SCHEMA_VERSION,
+            generator: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            summary: JsonStatistics::from(summary),
+            sources: tree
+                .children()
+                .iter()
+                .map(|child| JsonNode::new(osu, child, Path::new(""), &oszs, count_once).0)
+                .collect(),
+            unmatched: unmatched
+                .into_iter()
+                .filter_map(|err| match err {
+                    Error::NoBeatmapsetId(path) => Some(path.display().to_string()),
+                    _ => None,
+                })
+                .collect(),
+            problems: problems.into_iter().map(|err| err.to_string()).collect(),
+        }
+    }
+}
+
+impl JsonNode {
+    fn new(
+        osu: &dyn Osu,
+        tree: &PathTreeStylized<OsuBeatmapStatus>,
+        parent: &Path,
+        oszs: &Map<&Path, (&Osz, &OsuBeatmapStatus)>,
+        count_once: bool,
+    ) -> (Self, Vec<(u64, OsuBeatmapStatus)>) {
+        let path = parent.join(tree.segment());
+        let mut statuses = vec![];
+        let beatmapset = oszs.get(path.as_path()).map(|(osz, status)| {
+            statuses.push((osz.beatmapset_id, **status));
+            JsonBeatmapset::new(osu, osz)
+        });
+        let children = tree
+            .children()
+            .iter()
+            .map(|child| {
+                let (node, child_statuses) = JsonNode::new(osu, child, &path, oszs, count_once);
+                statuses.extend(child_statuses);
+                node
+            })
+            .collect();
+        let statistics = Statistics::from_statuses(
+            statuses
+                .iter()
+                .map(|(beatmapset_id, status)| (*beatmapset_id, status)),
+            count_once,
+        );
+        let node = Self {
+            segment: tree.segment().to_string(),
+            path: path.display().to_string(),
+            status: JsonStatus::from(tree.data().unwrap_or(&OsuBeatmapStatus::NotInstalled)),
+            statistics: JsonStatistics::from(&statistics),
+            beatmapset,
+            children,
+        };
+        (node, statuses)
+    }
+}
+
+impl JsonBeatmapset {
+    fn new(osu: &dyn Osu, osz: &Osz) -> Self {
+        Self {
+            id: osz.beatmapset_id,
+            archive: osz
+                .archive
+                .as_ref()
+                .map(|(archive, _)| archive.path.display().to_string()),
+            entry: osz.archive.as_ref().map(|(_, name)| name.clone()),
+            list: osz.list.as_ref().map(|list| list.display().to_string()),
+            size: osz.size,
+            crc32: osz.crc32,
+            difficulties: osu
+                .get_beatmapset(osz.beatmapset_id)
+                .map(|beatmapset| beatmapset.beatmaps())
+                .unwrap_or_default()
+                .iter()
+                .map(JsonDifficulty::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<&OsuBeatmap> for JsonDifficulty {
+    fn from(beatmap: &OsuBeatmap) -> Self {
+        Self {
+            id: beatmap.id(),
+            mode: beatmap.mode().map(mode_key),
+            stars: beatmap.stars(),
+            grades: MODES
+                .iter()
+                .map(|mode| (mode_key(*mode), JsonStatus::from(&beatmap.grade(*mode))))
+                .collect(),
+        }
+    }
+}
+
+impl From<&OsuBeatmapStatus> for JsonStatus {
+    fn from(status: &OsuBeatmapStatus) -> Self {
+        Self {
+            status: status_key(status),
+            grade: grade_key(status),
+        }
+    }
+}
+
+impl From<&Statistics> for JsonStatistics {
+    fn from(statistics: &Statistics) -> Self {
+        Self {
+            total: statistics.total(),
+            played: statistics.played,
+            not_played: statistics.not_played,
+            not_installed: statistics.not_installed,
+            percentage: statistics.percentage(),
+        }
+    }
+}
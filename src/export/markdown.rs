@@ -0,0 +1,128 @@
+use super::status_label;
+use crate::error::Error;
+use crate::osu::OsuBeatmapStatus;
+use crate::osz_finder::Osz;
+use crate::pathtree_stylizer::PathTreeStylized;
+use crate::statistics::Statistics;
+use std::collections::BTreeMap as Map;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(new)]
+pub struct MarkdownReport<'a> {
+    tree: &'a PathTreeStylized<OsuBeatmapStatus>,
+    statuses: &'a [(&'a Osz, &'a OsuBeatmapStatus)],
+    summary: &'a Statistics,
+    count_once: bool,
+    errors: &'a [Error],
+}
+
+impl MarkdownReport<'_> {
+    pub fn render(&self) -> String {
+        let oszs: Map<&Path, (&Osz, &OsuBeatmapStatus)> = self
+            .statuses
+            .iter()
+            .map(|(osz, status)| (osz.path.as_path(), (*osz, *status)))
+            .collect();
+        let mut markdown = String::new();
+        let _ = writeln!(
+            markdown,
+            "# Beatmapsets played\n\nSummary: {}\n",
+            escape(&self.summary.to_string())
+        );
+        for child in self.tree.children() {
+            let root = PathBuf::from(child.segment());
+            markdown
+                .push_str(&render_node(child, Path::new(""), &root, 0, &oszs, self.count_once).0);
+        }
+        if !self.errors.is_empty() {
+            let _ = writeln!(
+                markdown,
+                "## Problems found while scanning ({})\n",
+                self.errors.len()
+            );
+            for err in self.errors {
+                let _ = writeln!(markdown, "- {}", escape(&err.to_string()));
+            }
+        }
+        markdown
+    }
+}
+
+fn render_node(
+    tree: &PathTreeStylized<OsuBeatmapStatus>,
+    parent: &Path,
+    root: &Path,
+    depth: usize,
+    oszs: &Map<&Path, (&Osz, &OsuBeatmapStatus)>,
+    count_once: bool,
+) -> (String, Vec<(u64, OsuBeatmapStatus)>) {
+    let path = parent.join(tree.segment());
+    let status = tree.data().unwrap_or(&OsuBeatmapStatus::NotInstalled);
+    if let Some((osz, status)) = oszs.get(path.as_path()) {
+        let row = format!(
+            "| [{}](https://osu.ppy.sh/beatmapsets/{}) | {} |\n",
+            escape(tree.segment()),
+            osz.beatmapset_id,
+            badge(status)
+        );
+        return (row, vec![(osz.beatmapset_id, **status)]);
+    }
+    let mut rows = String::new();
+    let mut sections = String::new();
+    let mut statuses = vec![];
+    for child in tree.children() {
+        let (child_markdown, child_statuses) =
+            render_node(child, &path, root, depth + 1, oszs, count_once);
+        if oszs.contains_key(path.join(child.segment()).as_path()) {
+            rows.push_str(&child_markdown);
+        } else {
+            sections.push_str(&child_markdown);
+        }
+        statuses.extend(child_statuses);
+    }
+    let statistics = Statistics::from_statuses(
+        statuses
+            .iter()
+            .map(|(beatmapset_id, status)| (*beatmapset_id, status)),
+        count_once,
+    );
+    let title = match path.strip_prefix(root) {
+        Ok(relative) if depth > 0 => relative.display().to_string(),
+        _ => path.display().to_string(),
+    };
+    let mut markdown = format!(
+        "{} {} — {}/{} played ({:.1}%) {}\n\n",
+        "#".repeat((depth + 2).min(6)),
+        escape(&title),
+        statistics.played,
+        statistics.total(),
+        statistics.percentage(),
+        badge(status)
+    );
+    if !rows.is_empty() {
+        let _ = writeln!(markdown, "| Beatmapset | Status |\n| --- | --- |\n{}", rows);
+    }
+    markdown.push_str(&sections);
+    (markdown, statuses)
+}
+
+fn badge(status: &OsuBeatmapStatus) -> String {
+    let color = match status {
+        OsuBeatmapStatus::Played(_) => "🟢",
+        OsuBeatmapStatus::NotPlayed => "🔴",
+        OsuBeatmapStatus::NotInstalled => "🟡",
+    };
+    format!("{} {}", color, escape(&status_label(status)))
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\`*_[]<>|".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
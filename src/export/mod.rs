@@ -0,0 +1,93 @@
+mod html;
+mod json;
+mod markdown;
+mod table;
+mod thumbnail;
+
+pub use self::html::HtmlReport;
+pub use self::json::JsonReport;
+pub use self::markdown::MarkdownReport;
+pub use self::table::{Columns, TableWriter};
+pub use self::thumbnail::thumbnails;
+use crate::osu::{OsuBeatmapGrade, OsuBeatmapStatus, OsuMode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+    Tsv,
+    Html,
+    Markdown,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            "tsv" => Ok(OutputFormat::Tsv),
+            "html" => Ok(OutputFormat::Html),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            _ => Err(format!(
+                "{:?} is not one of text, json, csv, tsv, html or markdown",
+                s
+            )),
+        }
+    }
+}
+
+impl OutputFormat {
+    pub fn delimiter(&self) -> Option<u8> {
+        match self {
+            OutputFormat::Csv => Some(b','),
+            OutputFormat::Tsv => Some(b'\t'),
+            _ => None,
+        }
+    }
+}
+
+fn mode_key(mode: OsuMode) -> &'static str {
+    match mode {
+        OsuMode::Standard => "osu",
+        OsuMode::Taiko => "taiko",
+        OsuMode::Catch => "catch",
+        OsuMode::Mania => "mania",
+    }
+}
+
+fn status_key(status: &OsuBeatmapStatus) -> &'static str {
+    match status {
+        OsuBeatmapStatus::Played(_) => "played",
+        OsuBeatmapStatus::NotPlayed => "not_played",
+        OsuBeatmapStatus::NotInstalled => "not_installed",
+    }
+}
+
+fn status_label(status: &OsuBeatmapStatus) -> String {
+    match status {
+        OsuBeatmapStatus::Played(_) => {
+            format!("Played ({})", grade_key(status).unwrap_or_default())
+        }
+        OsuBeatmapStatus::NotPlayed => "Not played".to_string(),
+        OsuBeatmapStatus::NotInstalled => "Not installed".to_string(),
+    }
+}
+
+fn grade_key(status: &OsuBeatmapStatus) -> Option<&'static str> {
+    match status {
+        OsuBeatmapStatus::Played(grade) => Some(match grade {
+            OsuBeatmapGrade::SSSilver => "SSSilver",
+            OsuBeatmapGrade::SS => "SS",
+            OsuBeatmapGrade::SSilver => "SSilver",
+            OsuBeatmapGrade::S => "S",
+            OsuBeatmapGrade::A => "A",
+            OsuBeatmapGrade::B => "B",
+            OsuBeatmapGrade::C => "C",
+            OsuBeatmapGrade::D => "D",
+        }),
+        _ => None,
+    }
+}
@@ -0,0 +1,228 @@
+use super::{grade_key, mode_key, status_key};
+use crate::error::Error;
+use crate::osu::{Osu, OsuBeatmap, OsuBeatmapStatus};
+use crate::osz_finder::Osz;
+use crate::packs::Pack;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Path,
+    Pack,
+    BeatmapsetId,
+    Status,
+    Grade,
+    Played,
+    Difficulties,
+    MinStars,
+    MaxStars,
+    LastPlayed,
+    BeatmapId,
+    Difficulty,
+    Mode,
+    Stars,
+}
+
+const COLUMNS: [(&str, Column); 14] = [
+    ("path", Column::Path),
+    ("pack", Column::Pack),
+    ("beatmapset_id", Column::BeatmapsetId),
+    ("status", Column::Status),
+    ("grade", Column::Grade),
+    ("played", Column::Played),
+    ("difficulties", Column::Difficulties),
+    ("min_stars", Column::MinStars),
+    ("max_stars", Column::MaxStars),
+    ("last_played", Column::LastPlayed),
+    ("beatmap_id", Column::BeatmapId),
+    ("difficulty", Column::Difficulty),
+    ("mode", Column::Mode),
+    ("stars", Column::Stars),
+];
+
+impl Column {
+    fn name(&self) -> &'static str {
+        COLUMNS
+            .iter()
+            .find(|(_, column)| column == self)
+            .map(|(name, _)| *name)
+            .unwrap_or("")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, new)]
+pub struct Columns(pub Vec<Column>);
+
+impl std::str::FromStr for Columns {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(|name| {
+                let name = name.trim().to_lowercase();
+                COLUMNS
+                    .iter()
+                    .find(|(known, _)| *known == name)
+                    .map(|(_, column)| *column)
+                    .ok_or_else(|| {
+                        format!(
+                            "{:?} is not one of {}",
+                            name,
+                            COLUMNS
+                                .iter()
+                                .map(|(known, _)| *known)
+                                .collect::<Vec<&str>>()
+                                .join(", ")
+                        )
+                    })
+            })
+            .collect::<Result<Vec<Column>, String>>()
+            .map(Columns::new)
+    }
+}
+
+const DEFAULT_COLUMNS: [Column; 10] = [
+    Column::Path,
+    Column::Pack,
+    Column::BeatmapsetId,
+    Column::Status,
+    Column::Grade,
+    Column::Played,
+    Column::Difficulties,
+    Column::MinStars,
+    Column::MaxStars,
+    Column::LastPlayed,
+];
+
+const DEFAULT_DIFFICULTY_COLUMNS: [Column; 4] = [
+    Column::BeatmapId,
+    Column::Difficulty,
+    Column::Mode,
+    Column::Stars,
+];
+
+impl Columns {
+    pub fn defaults(per_difficulty: bool) -> Self {
+        let mut columns = DEFAULT_COLUMNS.to_vec();
+        if per_difficulty {
+            columns.extend(DEFAULT_DIFFICULTY_COLUMNS);
+        }
+        Self::new(columns)
+    }
+}
+
+#[derive(new)]
+pub struct TableWriter<'a> {
+    osu: &'a dyn Osu,
+    columns: Columns,
+    delimiter: u8,
+    per_difficulty: bool,
+}
+
+impl TableWriter<'_> {
+    pub fn write(
+        &self,
+        statuses: &[(&Osz, &OsuBeatmapStatus)],
+        writer: &mut dyn std::io::Write,
+    ) -> Result<(), Error> {
+        let mut table = csv::WriterBuilder::new()
+            .delimiter(self.delimiter)
+            .from_writer(writer);
+        let error = |err: csv::Error| Error::Export(err.to_string());
+        table
+            .write_record(self.columns.0.iter().map(|column| column.name()))
+            .map_err(error)?;
+        for (osz, status) in statuses {
+            let beatmaps = self
+                .osu
+                .get_beatmapset(osz.beatmapset_id)
+                .map(|beatmapset| beatmapset.beatmaps())
+                .unwrap_or_default();
+            let row = SetRow::new(osz, status, &beatmaps);
+            if self.per_difficulty && !beatmaps.is_empty() {
+                for beatmap in beatmaps.iter() {
+                    table
+                        .write_record(self.columns.0.iter().map(|c| row.cell(*c, Some(beatmap))))
+                        .map_err(error)?;
+                }
+            } else {
+                table
+                    .write_record(self.columns.0.iter().map(|c| row.cell(*c, None)))
+                    .map_err(error)?;
+            }
+        }
+        table.flush().map_err(|err| Error::Export(err.to_string()))
+    }
+}
+
+struct SetRow<'a> {
+    osz: &'a Osz,
+    status: &'a OsuBeatmapStatus,
+    played: usize,
+    difficulties: usize,
+    min_stars: Option<f64>,
+    max_stars: Option<f64>,
+    last_played: Option<String>,
+}
+
+impl<'a> SetRow<'a> {
+    fn new(osz: &'a Osz, status: &'a OsuBeatmapStatus, beatmaps: &[OsuBeatmap]) -> Self {
+        let stars: Vec<f64> = beatmaps
+            .iter()
+            .filter_map(|beatmap| beatmap.stars())
+            .collect();
+        Self {
+            osz,
+            status,
+            played: beatmaps
+                .iter()
+                .filter(|beatmap| matches!(beatmap.status(), OsuBeatmapStatus::Played(_)))
+                .count(),
+            difficulties: beatmaps.len(),
+            min_stars: stars.iter().copied().reduce(f64::min),
+            max_stars: stars.iter().copied().reduce(f64::max),
+            last_played: beatmaps
+                .iter()
+                .filter_map(|beatmap| beatmap.last_played())
+                .max(),
+        }
+    }
+
+    fn cell(&self, column: Column, beatmap: Option<&OsuBeatmap>) -> String {
+        let status = beatmap
+            .map(|beatmap| beatmap.status())
+            .unwrap_or(*self.status);
+        match column {
+            Column::Path => self.osz.path.display().to_string(),
+            Column::Pack => Pack::find(&self.osz.path)
+                .map(|pack| pack.code())
+                .unwrap_or_default(),
+            Column::BeatmapsetId => self.osz.beatmapset_id.to_string(),
+            Column::Status => status_key(&status).to_string(),
+            Column::Grade => grade_key(&status).unwrap_or_default().to_string(),
+            Column::Played => self.played.to_string(),
+            Column::Difficulties => self.difficulties.to_string(),
+            Column::MinStars => stars(self.min_stars),
+            Column::MaxStars => stars(self.max_stars),
+            Column::LastPlayed => match beatmap {
+                Some(beatmap) => beatmap.last_played().unwrap_or_default(),
+                None => self.last_played.clone().unwrap_or_default(),
+            },
+            Column::BeatmapId => beatmap
+                .map(|beatmap| beatmap.id().to_string())
+                .unwrap_or_default(),
+            Column::Difficulty => beatmap
+                .and_then(|beatmap| beatmap.name())
+                .unwrap_or_default(),
+            Column::Mode => beatmap
+                .and_then(|beatmap| beatmap.mode())
+                .map(|mode| mode_key(mode).to_string())
+                .unwrap_or_default(),
+            Column::Stars => stars(beatmap.and_then(|beatmap| beatmap.stars())),
+        }
+    }
+}
+
+fn stars(stars: Option<f64>) -> String {
+    stars
+        .map(|stars| format!("{:.2}", stars))
+        .unwrap_or_default()
+}
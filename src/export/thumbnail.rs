@@ -0,0 +1,163 @@
+use crate::error::Error;
+use crate::osu::Osu;
+use crate::osz_finder::{Osz, OszFinder};
+use base64::Engine;
+use std::collections::BTreeMap as Map;
+use std::collections::BTreeSet as Set;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+
+const WIDTH: u32 = 160;
+const HEIGHT: u32 = 90;
+
+type Zip = zip::ZipArchive<Cursor<Vec<u8>>>;
+
+pub fn thumbnails(finder: &OszFinder, osu: &dyn Osu, oszs: &[&Osz]) -> Map<u64, String> {
+    let songs = osu.get_songs_path().unwrap_or_default();
+    let library = osu.get_library().unwrap_or_default();
+    let mut thumbnails = Map::new();
+    let mut seen = Set::new();
+    let mut pending = vec![];
+    for osz in oszs {
+        if !seen.insert(osz.beatmapset_id) {
+            continue;
+        }
+        let installed = library
+            .get(&osz.beatmapset_id)
+            .and_then(|metadata| metadata.folder.as_ref())
+            .map(|folder| songs.join(folder))
+            .filter(|folder| folder.is_dir())
+            .and_then(|folder| from_folder(&folder));
+        match installed {
+            Some(thumbnail) => {
+                thumbnails.insert(osz.beatmapset_id, thumbnail);
+            }
+            None => pending.push((*osz).clone()),
+        }
+    }
+    for (osz, thumbnail) in finder.map_contents(&pending, from_osz) {
+        if let Ok(Some(thumbnail)) = thumbnail {
+            thumbnails.insert(osz.beatmapset_id, thumbnail);
+        }
+    }
+    thumbnails
+}
+
+fn from_folder(folder: &Path) -> Option<String> {
+    let mut charts: Vec<PathBuf> = std::fs::read_dir(folder)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| is_chart(&path.to_string_lossy()))
+        .collect();
+    charts.sort();
+    charts.iter().find_map(|chart| {
+        let background = background(&String::from_utf8_lossy(&std::fs::read(chart).ok()?))?;
+        thumbnail(&std::fs::read(resolve(folder, &background)?).ok()?)
+    })
+}
+
+fn from_osz(osz: &Osz, reader: &mut dyn Read) -> Result<Option<String>, Error> {
+    let mut content = vec![];
+    reader
+        .read_to_end(&mut content)
+        .map_err(|err| Error::Unextractable(osz.path.clone(), err.to_string()))?;
+    let corrupt = |err: zip::result::ZipError| Error::CorruptOsz(osz.path.clone(), err.to_string());
+    let mut zip = Zip::new(Cursor::new(content)).map_err(corrupt)?;
+    let mut names: Vec<String> = zip.file_names().map(String::from).collect();
+    names.sort();
+    for chart in names.iter().filter(|name| is_chart(name)) {
+        let chart = read_entry(&mut zip, chart).map_err(corrupt)?;
+        let background = match background(&String::from_utf8_lossy(&chart)) {
+            Some(background) => background,
+            None => continue,
+        };
+        let entry = names
+            .iter()
+            .find(|name| normalize(name).eq_ignore_ascii_case(&normalize(&background)));
+        let thumbnail = match entry {
+            Some(entry) => thumbnail(&read_entry(&mut zip, entry).map_err(corrupt)?),
+            None => None,
+        };
+        if thumbnail.is_some() {
+            return Ok(thumbnail);
+        }
+    }
+    Ok(None)
+}
+
+fn read_entry(zip: &mut Zip, name: &str) -> Result<Vec<u8>, zip::result::ZipError> {
+    let mut content = vec![];
+    zip.by_name(name)?.read_to_end(&mut content)?;
+    Ok(content)
+}
+
+fn is_chart(name: &str) -> bool {
+    name.to_lowercase().ends_with(".osu")
+}
+
+fn background(chart: &str) -> Option<String> {
+    let mut events = false;
+    for line in chart.lines().map(str::trim) {
+        if line.starts_with('[') {
+            events = line == "[Events]";
+            continue;
+        }
+        if !events || line.starts_with("//") {
+            continue;
+        }
+        let mut fields = line.split(',');
+        if fields.next().map(str::trim) == Some("0") {
+            let name = fields.nth(1)?.trim().trim_matches('"');
+            if !name.is_empty() {
+                return Some(name.to_string());
+            }
+        }
+    }
+    None
+}
+
+fn normalize(name: &str) -> String {
+    name.replace('\\', "/").trim_start_matches("./").to_string()
+}
+
+fn resolve(folder: &Path, relative: &str) -> Option<PathBuf> {
+    let mut path = folder.to_path_buf();
+    for component in relative
+        .split(['/', '\\'])
+        .filter(|component| !component.is_empty() && *component != ".")
+    {
+        if component == ".." {
+            return None;
+        }
+        let exact = path.join(component);
+        path = if exact.exists() {
+            exact
+        } else {
+            std::fs::read_dir(&path)
+                .ok()?
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .find(|path| {
+                    path.file_name()
+                        .is_some_and(|name| name.to_string_lossy().eq_ignore_ascii_case(component))
+                })?
+        };
+    }
+    Some(path)
+}
+
+fn thumbnail(image: &[u8]) -> Option<String> {
+    let thumbnail = image::load_from_memory(image)
+        .ok()?
+        .thumbnail(WIDTH, HEIGHT)
+        .to_rgb8();
+    let mut jpeg = Cursor::new(vec![]);
+    thumbnail
+        .write_to(&mut jpeg, image::ImageOutputFormat::Jpeg(75))
+        .ok()?;
+    Some(format!(
+        "data:image/jpeg;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(jpeg.into_inner())
+    ))
+}
@@ -0,0 +1,127 @@
+use crate::error::Error;
+use crate::osz_finder::{Osz, OszFinder};
+use std::collections::BTreeSet as Set;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum InstallAction {
+    #[display(fmt = "installed")]
+    Installed,
+    #[display(fmt = "would be installed")]
+    WouldInstall,
+    #[display(fmt = "already present")]
+    AlreadyPresent,
+}
+
+#[derive(Debug, new)]
+pub struct Installation {
+    pub osz: Osz,
+    pub destination: PathBuf,
+    pub action: Result<InstallAction, Error>,
+}
+
+#[derive(Debug, Clone, new)]
+pub struct Installer {
+    target: PathBuf,
+    dry_run: bool,
+    limit: Option<usize>,
+}
+
+impl Installer {
+    pub fn install(&self, finder: &OszFinder, oszs: &[&Osz]) -> Result<Vec<Installation>, Error> {
+        let mut present = self.present(finder)?;
+        let mut installations: Vec<Installation> = vec![];
+        let mut installed = 0;
+        let mut next = 0;
+        while next < oszs.len() && self.below_limit(installed) {
+            // Copies are batched so that each archive is read once per batch. A
+            // batch stops before a beatmapset it already holds: whether a later
+            // '.osz' is needed depends on the earlier one being copied.
+            let mut batch: Vec<usize> = vec![];
+            let mut batch_ids = Set::new();
+            while next < oszs.len()
+                && self.below_limit(installed + batch.len())
+                && !batch_ids.contains(&oszs[next].beatmapset_id)
+            {
+                let osz = oszs[next];
+                next += 1;
+                let destination = self.destination(osz);
+                let action = if present.contains(&osz.beatmapset_id) || destination.exists() {
+                    Ok(InstallAction::AlreadyPresent)
+                } else if self.dry_run {
+                    installed += 1;
+                    present.insert(osz.beatmapset_id);
+                    Ok(InstallAction::WouldInstall)
+                } else {
+                    batch.push(installations.len());
+                    batch_ids.insert(osz.beatmapset_id);
+                    Ok(InstallAction::Installed)
+                };
+                installations.push(Installation::new((*osz).clone(), destination, action));
+            }
+            let batch_oszs: Vec<Osz> = batch
+                .iter()
+                .map(|index| installations[*index].osz.clone())
+                .collect();
+            let copied = finder.map_contents(&batch_oszs, |osz, reader| {
+                copy_osz(osz, reader, &self.destination(osz))
+            });
+            for (index, (_, copied)) in batch.into_iter().zip(copied) {
+                let installation = &mut installations[index];
+                match copied {
+                    Ok(()) => {
+                        installed += 1;
+                        present.insert(installation.osz.beatmapset_id);
+                    }
+                    Err(err) => installation.action = Err(err),
+                }
+            }
+        }
+        Ok(installations)
+    }
+
+    fn below_limit(&self, installed: usize) -> bool {
+        match self.limit {
+            Some(limit) => installed < limit,
+            None => true,
+        }
+    }
+
+    fn destination(&self, osz: &Osz) -> PathBuf {
+        self.target.join(osz.path.file_name().unwrap_or_default())
+    }
+
+    fn present(&self, finder: &OszFinder) -> Result<Set<u64>, Error> {
+        let mut present = Set::new();
+        let entries = self
+            .target
+            .read_dir()
+            .map_err(|err| Error::Io(self.target.clone(), err))?;
+        for entry in entries {
+            let entry = entry.map_err(|err| Error::Io(self.target.clone(), err))?;
+            if let Ok(id) = finder.beatmapset_id(&entry.path()) {
+                present.insert(id);
+            }
+        }
+        Ok(present)
+    }
+}
+
+pub fn copy_osz(osz: &Osz, reader: &mut dyn Read, destination: &Path) -> Result<(), Error> {
+    let partial = destination.with_extension("osz.part");
+    let copied = std::fs::File::create(&partial)
+        .map_err(|err| Error::Io(partial.clone(), err))
+        .and_then(|mut file| {
+            std::io::copy(reader, &mut file)
+                .map_err(|err| Error::Unextractable(osz.path.clone(), err.to_string()))
+        })
+        .and_then(|_| {
+            std::fs::rename(&partial, destination)
+                .map_err(|err| Error::Io(destination.to_path_buf(), err))
+        });
+    if copied.is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+    copied
+}
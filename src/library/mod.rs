@@ -0,0 +1,68 @@
+use crate::error::Error;
+use crate::osu::{Osu, OsuBeatmapSetMetadata};
+use crate::osz_finder::{Osz, OszScan};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LibraryGrouping {
+    Artist,
+    Creator,
+    Letter,
+}
+
+impl std::str::FromStr for LibraryGrouping {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "artist" => Ok(LibraryGrouping::Artist),
+            "creator" | "mapper" => Ok(LibraryGrouping::Creator),
+            "letter" => Ok(LibraryGrouping::Letter),
+            _ => Err(format!("{:?} is not one of artist, creator or letter", s)),
+        }
+    }
+}
+
+impl LibraryGrouping {
+    fn group(&self, metadata: &OsuBeatmapSetMetadata) -> String {
+        let group = match self {
+            LibraryGrouping::Artist => metadata.artist.clone(),
+            LibraryGrouping::Creator => metadata.creator.clone(),
+            LibraryGrouping::Letter => metadata
+                .title
+                .chars()
+                .find(|c| c.is_alphanumeric())
+                .filter(|c| c.is_alphabetic())
+                .map(|c| c.to_uppercase().to_string())
+                .unwrap_or_else(|| "#".to_string()),
+        };
+        segment(&group)
+    }
+}
+
+pub fn find_library_oszs(osu: &dyn Osu, grouping: LibraryGrouping) -> Result<OszScan, Error> {
+    let library = osu.get_library()?;
+    let songs = osu.get_songs_path().ok_or(Error::UnsupportedLibrary)?;
+    let mut scan = OszScan::default();
+    for (beatmapset_id, metadata) in library {
+        let name = metadata.folder.clone().unwrap_or_else(|| {
+            format!("{} {} - {}", beatmapset_id, metadata.artist, metadata.title)
+        });
+        let path = songs.join(grouping.group(&metadata)).join(segment(&name));
+        scan.oszs.push(Osz::new(
+            path,
+            beatmapset_id,
+            None,
+            None,
+            None,
+            Some(songs.clone()),
+        ));
+    }
+    Ok(scan)
+}
+
+fn segment(name: &str) -> String {
+    let segment = name.replace(['/', '\\'], "_");
+    match segment.trim() {
+        "" | "." | ".." => "(unknown)".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
@@ -1,19 +1,48 @@
 #[macro_use]
+extern crate derive_more;
+#[macro_use]
 extern crate derive_new;
 
 mod cli;
+mod duplicates;
+mod error;
+mod export;
+mod install;
+mod library;
 mod osu;
 mod osz_finder;
+mod packs;
 mod pathtree_stylizer;
+mod staging;
+mod statistics;
+mod watch;
 
+use crate::cli::CliArguments;
+use crate::error::Error;
+use crate::export::{
+    thumbnails, Columns, HtmlReport, JsonReport, MarkdownReport, OutputFormat, TableWriter,
+};
+use crate::install::{InstallAction, Installer};
+use crate::library::find_library_oszs;
 use crate::osu::{Osu, Osu40, Osu50, OsuBeatmapGrade, OsuBeatmapStatus};
-use crate::osz_finder::find_oszs;
+use crate::osz_finder::{
+    source_of, ArchiveCache, IdPatterns, Limits, Osz, OszFinder, OszScan, Passwords, PathFilter,
+    DEFAULT_ID_PATTERNS,
+};
+use crate::packs::{Catalogue, Pack, PackSeries};
 use crate::pathtree_stylizer::PathTreeStylized;
+use crate::staging::{Stager, StagingAction, StagingFilter};
+use crate::statistics::Statistics;
+use std::collections::BTreeMap as Map;
+use std::collections::BTreeSet as Set;
 use std::convert::TryFrom;
-use std::path::PathBuf;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 
-type ResultOsuOpener = Result<Box<dyn Osu>, String>;
+type ResultOsuOpener = Result<Box<dyn Osu>, Error>;
 type FnOsuOpener = dyn Fn(&PathBuf) -> ResultOsuOpener;
+type ResultScan = Result<(Box<dyn Osu>, OszScan, Vec<(PathBuf, OsuBeatmapStatus)>), Error>;
+type PackStatuses<'a> = Map<PackSeries, Map<u64, (Pack, Vec<(u64, &'a OsuBeatmapStatus)>)>>;
 
 const FN_OSU_OPENER: [&FnOsuOpener; 1] = [
     //2] = [
@@ -21,160 +50,604 @@ const FN_OSU_OPENER: [&FnOsuOpener; 1] = [
     // &(|x| Osu50::try_from(x).map(|a| a.boxed())),
 ];
 
-fn main() -> Result<(), String> {
-    let args = crate::cli::get_arguments_parsed();
-    if !args.packs_source.is_dir() && !args.packs_source.is_file() {
-        return Err(format!(
-            "{:?} is neither a directory nor a file",
-            args.packs_source
-        ));
-    }
-    let osu_open_result: Vec<ResultOsuOpener> =
-        FN_OSU_OPENER.iter().map(|x| x(&args.osu_source)).collect();
-    let osu_open_successes: Option<&Box<dyn Osu>> = osu_open_result
-        .iter()
-        .filter_map(|x| x.as_ref().ok())
-        .next();
-    let osu_open_errors: Vec<&String> = osu_open_result
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Error> {
+    let mut args = crate::cli::get_arguments_parsed();
+    crate::cli::expand_stdin_sources(&mut args)?;
+    if args.packs_sources.is_empty() && args.library.is_none() {
+        return Err(Error::NoSources);
+    }
+    if args.format.delimiter().is_none() {
+        if args.columns.is_some() {
+            return Err(Error::TableOnlyOption("--columns"));
+        }
+        if args.per_difficulty {
+            return Err(Error::TableOnlyOption("--per-difficulty"));
+        }
+    }
+    for packs_source in args
+        .packs_sources
         .iter()
-        .filter_map(|x| x.as_ref().err())
-        .collect();
-    if let Some(osu) = osu_open_successes {
-        let oszs = find_oszs(&args.packs_source);
-        if oszs.len() == 0 {
-            return Err(format!("{:?} contains no beatmapset", args.packs_source));
+        .chain(args.listed_paths.values().flatten())
+    {
+        if !packs_source.is_dir() && !packs_source.is_file() {
+            return Err(Error::NotFound(packs_source.clone()));
+        }
+    }
+    let cache = if args.no_cache {
+        None
+    } else {
+        args.cache_file
+            .clone()
+            .or_else(ArchiveCache::default_path)
+            .and_then(|cache_path| {
+                ArchiveCache::open(&cache_path)
+                    .and_then(|cache| {
+                        if args.rebuild_cache {
+                            cache.clear()?;
+                        }
+                        Ok(cache)
+                    })
+                    .map_err(|err| eprintln!("{}", err))
+                    .ok()
+            })
+    };
+    let passwords = match &args.passwords {
+        Some(path) => Passwords::load(path)?,
+        None => Passwords::default(),
+    };
+    let catalogue = args.catalogue.as_ref().map(Catalogue::load).transpose()?;
+    let limits = Limits::new(
+        args.max_entries,
+        args.max_size.saturating_mul(1024 * 1024),
+        args.max_entry_depth,
+        Some(std::time::Duration::from_secs(args.archive_timeout))
+            .filter(|timeout| !timeout.is_zero()),
+    );
+    let default_id_patterns: &[&str] = if args.no_default_id_patterns {
+        &[]
+    } else {
+        &DEFAULT_ID_PATTERNS
+    };
+    let id_patterns = IdPatterns::compile(
+        args.id_patterns
+            .iter()
+            .map(String::as_str)
+            .chain(default_id_patterns.iter().copied()),
+    )?;
+    let filter = PathFilter::compile(&args.includes, &args.excludes)?;
+    let mut finder = OszFinder::new(args.threads, cache, passwords, limits, id_patterns, filter);
+    if args.install {
+        install(&args, &mut finder)
+    } else if let Some(target) = &args.stage {
+        stage(&args, &mut finder, target)
+    } else if args.watch {
+        let clear = args.format == OutputFormat::Text && std::io::stdout().is_terminal();
+        crate::watch::watch(
+            &args.osu_source,
+            &args.packs_sources,
+            std::time::Duration::from_millis(args.watch_debounce),
+            || {
+                if clear {
+                    print!("\x1b[2J\x1b[H");
+                }
+                if let Err(err) = report(&args, &mut finder, catalogue.as_ref()) {
+                    eprintln!("{}", err);
+                }
+            },
+        )
+    } else {
+        report(&args, &mut finder, catalogue.as_ref())
+    }
+}
+
+fn scan(args: &CliArguments, finder: &mut OszFinder) -> ResultScan {
+    let (osu_open_successes, osu_open_errors): (Vec<ResultOsuOpener>, Vec<ResultOsuOpener>) =
+        FN_OSU_OPENER
+            .iter()
+            .map(|x| x(&args.osu_source))
+            .partition(|x| x.is_ok());
+    if let Some(Ok(osu)) = osu_open_successes.into_iter().next() {
+        let mut scan = OszScan::default();
+        for packs_source in args.packs_sources.iter() {
+            let paths = match args.listed_paths.get(packs_source) {
+                Some(paths) => paths.as_slice(),
+                None => std::slice::from_ref(packs_source),
+            };
+            scan.append(finder.find_oszs(packs_source, paths));
+        }
+        if let Some(grouping) = args.library {
+            scan.append(find_library_oszs(osu.as_ref(), grouping)?);
+        }
+        if scan.oszs.is_empty() {
+            Err(Error::NoBeatmapsets(sources(args, osu.as_ref())))
         } else {
-            let osz_ids: Vec<(&PathBuf, u64)> = oszs
+            let osz_statuses: Vec<(PathBuf, OsuBeatmapStatus)> = scan
+                .oszs
                 .iter()
-                .map(|osz_path: &PathBuf| {
+                .map(|osz: &Osz| {
                     (
-                        osz_path,
-                        osz_path
-                            .file_stem()
-                            .unwrap()
-                            .to_str()
-                            .unwrap()
-                            .chars()
-                            .take_while(|c| c.is_numeric())
-                            .collect::<String>()
-                            .parse::<u64>()
-                            .unwrap(),
-                    )
-                })
-                .collect();
-            let osz_statuses: Vec<(PathBuf, OsuBeatmapStatus)> = osz_ids
-                .iter()
-                .map(|(osz_path, bms_id): &(&PathBuf, u64)| {
-                    (
-                        (*osz_path).clone(),
-                        osu.get_beatmapset(*bms_id)
+                        osz.path.clone(),
+                        osu.get_beatmapset(osz.beatmapset_id)
                             .and_then(|bms| Some(bms.worst_rank()))
                             .unwrap_or(OsuBeatmapStatus::NotInstalled),
                     )
                 })
                 .collect();
-            let mut pathtree_stylized = PathTreeStylized::from(&osz_statuses);
-            pathtree_stylized.fill_data_greatest();
-            pathtree_stylized.sort();
-            pathtree_stylized.reverse();
-            let style_obviously_pending = ansi_term::Style::new()
-                .bold()
-                .fg(ansi_term::Color::Red)
-                .on(ansi_term::Color::Yellow);
-            let style_pending = ansi_term::Style::new().bold().fg(ansi_term::Color::Red);
-            let style_done = ansi_term::Style::new().bold().fg(ansi_term::Color::Green);
-            let style_base = ansi_term::Style::new().dimmed();
-            pathtree_stylized.set_colors(
-                Some(
-                    vec![
-                        (
-                            OsuBeatmapStatus::NotInstalled,
-                            (
-                                style_obviously_pending.prefix().to_string(),
-                                style_obviously_pending.suffix().to_string(),
-                            ),
-                        ),
-                        (
-                            OsuBeatmapStatus::NotPlayed,
-                            (
-                                style_pending.prefix().to_string(),
-                                style_pending.suffix().to_string(),
-                            ),
-                        ),
-                        (
-                            OsuBeatmapStatus::Played(OsuBeatmapGrade::SSSilver),
-                            (
-                                style_done.prefix().to_string(),
-                                style_done.suffix().to_string(),
-                            ),
-                        ),
-                        (
-                            OsuBeatmapStatus::Played(OsuBeatmapGrade::SSilver),
-                            (
-                                style_done.prefix().to_string(),
-                                style_done.suffix().to_string(),
-                            ),
-                        ),
-                        (
-                            OsuBeatmapStatus::Played(OsuBeatmapGrade::SS),
-                            (
-                                style_done.prefix().to_string(),
-                                style_done.suffix().to_string(),
-                            ),
-                        ),
-                        (
-                            OsuBeatmapStatus::Played(OsuBeatmapGrade::S),
-                            (
-                                style_done.prefix().to_string(),
-                                style_done.suffix().to_string(),
-                            ),
-                        ),
-                        (
-                            OsuBeatmapStatus::Played(OsuBeatmapGrade::A),
-                            (
-                                style_done.prefix().to_string(),
-                                style_done.suffix().to_string(),
-                            ),
-                        ),
-                        (
-                            OsuBeatmapStatus::Played(OsuBeatmapGrade::B),
-                            (
-                                style_done.prefix().to_string(),
-                                style_done.suffix().to_string(),
-                            ),
-                        ),
-                        (
-                            OsuBeatmapStatus::Played(OsuBeatmapGrade::C),
-                            (
-                                style_done.prefix().to_string(),
-                                style_done.suffix().to_string(),
-                            ),
-                        ),
-                        (
-                            OsuBeatmapStatus::Played(OsuBeatmapGrade::D),
-                            (
-                                style_done.prefix().to_string(),
-                                style_done.suffix().to_string(),
-                            ),
-                        ),
-                    ]
-                    .into_iter()
-                    .collect(),
-                ),
-                Some((
-                    style_base.prefix().to_string(),
-                    style_base.suffix().to_string(),
-                )),
-            );
-            // println!("{:#?}", pathtree_stylized);
-            println!("{}", pathtree_stylized);
+            Ok((osu, scan, osz_statuses))
         }
     } else {
-        return Err(osu_open_errors
+        Err(Error::NoOsuInstallation(
+            osu_open_errors
+                .into_iter()
+                .filter_map(|x| x.err())
+                .collect(),
+        ))
+    }
+}
+
+fn sources(args: &CliArguments, osu: &dyn Osu) -> Vec<PathBuf> {
+    let mut sources = args.packs_sources.clone();
+    if args.library.is_some() {
+        sources.extend(osu.get_songs_path());
+    }
+    sources
+}
+
+fn install(args: &CliArguments, finder: &mut OszFinder) -> Result<(), Error> {
+    let target = match &args.install_to {
+        Some(target) => target.clone(),
+        None => args.osu_source.join("Songs"),
+    };
+    if !target.is_dir() {
+        return Err(Error::NotADirectory(target));
+    }
+    let (_, scan, osz_statuses) = scan(args, finder)?;
+    let pending: Vec<&Osz> = scan
+        .oszs
+        .iter()
+        .zip(osz_statuses.iter())
+        .filter(|(osz, (_, status))| {
+            *status == OsuBeatmapStatus::NotInstalled && osz.list.is_none()
+        })
+        .map(|(osz, _)| osz)
+        .collect();
+    let installer = Installer::new(target, args.dry_run, args.limit);
+    let installations = installer.install(finder, &pending)?;
+    let mut failures = 0;
+    for installation in installations.iter() {
+        match &installation.action {
+            Ok(InstallAction::AlreadyPresent) => {}
+            Ok(action) => println!(
+                "{} {} ({})",
+                installation.osz.path.display(),
+                action,
+                installation.destination.display()
+            ),
+            Err(err) => {
+                failures += 1;
+                println!("{}", err);
+            }
+        }
+    }
+    let count = |expected: InstallAction| {
+        installations
+            .iter()
+            .filter(|installation| installation.action.as_ref().ok() == Some(&expected))
+            .count()
+    };
+    println!(
+        "{} installed, {} would be installed, {} already present, {} failed",
+        count(InstallAction::Installed),
+        count(InstallAction::WouldInstall),
+        count(InstallAction::AlreadyPresent),
+        failures
+    );
+    if failures > 0 {
+        return Err(Error::InstallFailed(failures));
+    }
+    Ok(())
+}
+
+fn stage(args: &CliArguments, finder: &mut OszFinder, target: &Path) -> Result<(), Error> {
+    let (osu, scan, osz_statuses) = scan(args, finder)?;
+    let filter = StagingFilter::new(
+        args.packs.clone(),
+        args.modes.clone(),
+        args.min_stars,
+        args.max_stars,
+    );
+    let pending: Vec<&Osz> = scan
+        .oszs
+        .iter()
+        .zip(osz_statuses.iter())
+        .filter(|(_, (_, status))| !matches!(status, OsuBeatmapStatus::Played(_)))
+        .map(|(osz, _)| osz)
+        .filter(|osz| osz.list.is_none() && filter.matches(osu.as_ref(), osz))
+        .collect();
+    let stager = Stager::new(
+        args.packs_sources.clone(),
+        target.to_path_buf(),
+        args.hard_link,
+        args.dry_run,
+        args.limit,
+    );
+    let stagings = stager.stage(finder, &pending);
+    let mut failures = 0;
+    for staging in stagings.iter() {
+        match &staging.action {
+            Ok(StagingAction::AlreadyStaged) => {}
+            Ok(action) => println!(
+                "{} {} ({})",
+                staging.osz.path.display(),
+                action,
+                staging.destination.display()
+            ),
+            Err(err) => {
+                failures += 1;
+                println!("{}", err);
+            }
+        }
+    }
+    let count = |expected: &[StagingAction]| {
+        stagings
+            .iter()
+            .filter(|staging| {
+                staging
+                    .action
+                    .as_ref()
+                    .is_ok_and(|action| expected.contains(action))
+            })
+            .count()
+    };
+    println!(
+        "{} staged, {} would be staged, {} already staged, {} failed",
+        count(&[StagingAction::Copied, StagingAction::Linked]),
+        count(&[StagingAction::WouldStage]),
+        count(&[StagingAction::AlreadyStaged]),
+        failures
+    );
+    if failures > 0 {
+        return Err(Error::StageFailed(failures));
+    }
+    Ok(())
+}
+
+fn report(
+    args: &CliArguments,
+    finder: &mut OszFinder,
+    catalogue: Option<&Catalogue>,
+) -> Result<(), Error> {
+    let (osu, scan, osz_statuses) = scan(args, finder)?;
+    let sources = sources(args, osu.as_ref());
+    let mut pathtree_stylized = PathTreeStylized::try_from((sources.as_slice(), &osz_statuses))?;
+    pathtree_stylized.fill_data_greatest();
+    pathtree_stylized.sort();
+    pathtree_stylized.reverse();
+    let statuses: Vec<(&Osz, &OsuBeatmapStatus)> = scan
+        .oszs
+        .iter()
+        .zip(osz_statuses.iter().map(|(_, status)| status))
+        .collect();
+    let statistics = Statistics::from_statuses(
+        statuses
+            .iter()
+            .map(|(osz, status)| (osz.beatmapset_id, *status)),
+        args.count_once,
+    );
+    if args.format == OutputFormat::Json {
+        let report = JsonReport::new(
+            osu.as_ref(),
+            &pathtree_stylized,
+            &statuses,
+            &statistics,
+            args.count_once,
+            &scan.errors,
+        );
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).map_err(|err| Error::Export(err.to_string()))?
+        );
+        return Ok(());
+    }
+    if args.format == OutputFormat::Html {
+        let thumbnails = if args.thumbnails {
+            let oszs: Vec<&Osz> = statuses.iter().map(|(osz, _)| *osz).collect();
+            thumbnails(finder, osu.as_ref(), &oszs)
+        } else {
+            Map::new()
+        };
+        print!(
+            "{}",
+            HtmlReport::new(
+                &pathtree_stylized,
+                &statuses,
+                &statistics,
+                args.count_once,
+                &scan.errors,
+                &thumbnails
+            )
+            .render()
+        );
+        return Ok(());
+    }
+    if args.format == OutputFormat::Markdown {
+        print!(
+            "{}",
+            MarkdownReport::new(
+                &pathtree_stylized,
+                &statuses,
+                &statistics,
+                args.count_once,
+                &scan.errors
+            )
+            .render()
+        );
+        return Ok(());
+    }
+    if let Some(delimiter) = args.format.delimiter() {
+        let columns = args
+            .columns
+            .clone()
+            .unwrap_or_else(|| Columns::defaults(args.per_difficulty));
+        return TableWriter::new(osu.as_ref(), columns, delimiter, args.per_difficulty)
+            .write(&statuses, &mut std::io::stdout().lock());
+    }
+    let style_obviously_pending = ansi_term::Style::new()
+        .bold()
+        .fg(ansi_term::Color::Red)
+        .on(ansi_term::Color::Yellow);
+    let style_pending = ansi_term::Style::new().bold().fg(ansi_term::Color::Red);
+    let style_done = ansi_term::Style::new().bold().fg(ansi_term::Color::Green);
+    let style_base = ansi_term::Style::new().dimmed();
+    pathtree_stylized.set_colors(
+        Some(
+            vec![
+                (
+                    OsuBeatmapStatus::NotInstalled,
+                    (
+                        style_obviously_pending.prefix().to_string(),
+                        style_obviously_pending.suffix().to_string(),
+                    ),
+                ),
+                (
+                    OsuBeatmapStatus::NotPlayed,
+                    (
+                        style_pending.prefix().to_string(),
+                        style_pending.suffix().to_string(),
+                    ),
+                ),
+                (
+                    OsuBeatmapStatus::Played(OsuBeatmapGrade::SSSilver),
+                    (
+                        style_done.prefix().to_string(),
+                        style_done.suffix().to_string(),
+                    ),
+                ),
+                (
+                    OsuBeatmapStatus::Played(OsuBeatmapGrade::SSilver),
+                    (
+                        style_done.prefix().to_string(),
+                        style_done.suffix().to_string(),
+                    ),
+                ),
+                (
+                    OsuBeatmapStatus::Played(OsuBeatmapGrade::SS),
+                    (
+                        style_done.prefix().to_string(),
+                        style_done.suffix().to_string(),
+                    ),
+                ),
+                (
+                    OsuBeatmapStatus::Played(OsuBeatmapGrade::S),
+                    (
+                        style_done.prefix().to_string(),
+                        style_done.suffix().to_string(),
+                    ),
+                ),
+                (
+                    OsuBeatmapStatus::Played(OsuBeatmapGrade::A),
+                    (
+                        style_done.prefix().to_string(),
+                        style_done.suffix().to_string(),
+                    ),
+                ),
+                (
+                    OsuBeatmapStatus::Played(OsuBeatmapGrade::B),
+                    (
+                        style_done.prefix().to_string(),
+                        style_done.suffix().to_string(),
+                    ),
+                ),
+                (
+                    OsuBeatmapStatus::Played(OsuBeatmapGrade::C),
+                    (
+                        style_done.prefix().to_string(),
+                        style_done.suffix().to_string(),
+                    ),
+                ),
+                (
+                    OsuBeatmapStatus::Played(OsuBeatmapGrade::D),
+                    (
+                        style_done.prefix().to_string(),
+                        style_done.suffix().to_string(),
+                    ),
+                ),
+            ]
             .into_iter()
-            .cloned()
-            .collect::<Vec<String>>()
-            .join(", "));
+            .collect(),
+        ),
+        Some((
+            style_base.prefix().to_string(),
+            style_base.suffix().to_string(),
+        )),
+    );
+    // println!("{:#?}", pathtree_stylized);
+    println!("{}", pathtree_stylized);
+    if args.count_once || sources.len() > 1 {
+        println!("Summary: {}", statistics);
+    }
+    if sources.len() > 1 {
+        for source in sources.iter() {
+            let statistics = Statistics::from_statuses(
+                statuses
+                    .iter()
+                    .filter(|(osz, _)| source_of(&sources, &osz.path) == Some(source))
+                    .map(|(osz, status)| (osz.beatmapset_id, *status)),
+                args.count_once,
+            );
+            println!(" - {}: {}", source.display(), statistics);
+        }
+    }
+    let mut packs: PackStatuses = Map::new();
+    for (osz, status) in statuses.iter() {
+        if let Some(pack) = Pack::find(&osz.path) {
+            packs
+                .entry(pack.series)
+                .or_default()
+                .entry(pack.number)
+                .or_insert_with(|| (pack, vec![]))
+                .1
+                .push((osz.beatmapset_id, *status));
+        }
+    }
+    if !packs.is_empty() {
+        println!("Packs:");
+        for (series, numbers) in packs.iter() {
+            let statistics = Statistics::from_statuses(
+                numbers
+                    .values()
+                    .flat_map(|(_, statuses)| statuses.iter().copied()),
+                args.count_once,
+            );
+            println!(
+                " - {} ({} pack{}): {}",
+                series,
+                numbers.len(),
+                if numbers.len() == 1 { "" } else { "s" },
+                statistics
+            );
+            for (pack, statuses) in numbers.values() {
+                let statistics =
+                    Statistics::from_statuses(statuses.iter().copied(), args.count_once);
+                println!("    - {}: {}", pack, statistics);
+            }
+        }
+    }
+    if let Some(catalogue) = catalogue {
+        print_catalogue(catalogue, &packs, &scan.oszs);
+    }
+    if args.duplicates {
+        let duplicates = crate::duplicates::find_duplicates(finder, &scan.oszs);
+        println!("Duplicate beatmapsets ({}):", duplicates.len());
+        for duplicate in duplicates.iter() {
+            println!(
+                " - {} ({})",
+                duplicate.beatmapset_id,
+                match duplicate.contents_differ() {
+                    Some(true) => "contents differ",
+                    Some(false) => "identical contents",
+                    None => "contents could not be compared",
+                }
+            );
+            for osz in duplicate.oszs.iter() {
+                println!(
+                    "    - {} ({}, crc32 {})",
+                    osz.path.display(),
+                    osz.size
+                        .map(|size| format!("{} bytes", size))
+                        .unwrap_or_else(|| "unknown size".to_string()),
+                    osz.crc32
+                        .map(|crc32| format!("{:08x}", crc32))
+                        .unwrap_or_else(|| "unknown".to_string()),
+                );
+            }
+        }
+    }
+    if args.verify {
+        let verifications = finder.verify(&scan.oszs);
+        let corrupt: Vec<&Error> = verifications
+            .iter()
+            .filter_map(|(_, verification)| verification.as_ref().err())
+            .collect();
+        println!(
+            "Corrupt or truncated '.osz's ({} of {}):",
+            corrupt.len(),
+            verifications.len()
+        );
+        for err in corrupt {
+            println!(" - {}", err);
+        }
+    }
+    let (unmatched, problems): (Vec<&Error>, Vec<&Error>) = scan
+        .errors
+        .iter()
+        .partition(|err| matches!(err, Error::NoBeatmapsetId(_)));
+    if !unmatched.is_empty() {
+        println!("'.osz's matching no id pattern ({}):", unmatched.len());
+        for err in unmatched {
+            if let Error::NoBeatmapsetId(path) = err {
+                println!(" - {}", path.display());
+            }
+        }
+    }
+    if !problems.is_empty() {
+        println!("Problems found while scanning ({}):", problems.len());
+        for err in problems {
+            println!(" - {}", err);
+        }
     }
     Ok(())
 }
+
+fn print_catalogue(catalogue: &Catalogue, packs: &PackStatuses, oszs: &[Osz]) {
+    let library: Set<u64> = oszs.iter().map(|osz| osz.beatmapset_id).collect();
+    let mut missing_packs = vec![];
+    let mut incomplete_packs = vec![];
+    for ((series, number), catalogue_pack) in catalogue.packs.iter() {
+        match packs.get(series).and_then(|numbers| numbers.get(number)) {
+            Some((_, statuses)) => {
+                let found: Set<u64> = statuses.iter().map(|(id, _)| *id).collect();
+                let missing: Vec<_> = catalogue_pack
+                    .beatmapsets
+                    .iter()
+                    .filter(|beatmapset| !found.contains(&beatmapset.id))
+                    .collect();
+                if !missing.is_empty() {
+                    incomplete_packs.push((catalogue_pack, missing));
+                }
+            }
+            None => missing_packs.push(catalogue_pack),
+        }
+    }
+    println!("Packs missing from your library ({}):", missing_packs.len());
+    for catalogue_pack in missing_packs {
+        println!(
+            " - {} ({} beatmapsets)",
+            catalogue_pack.pack,
+            catalogue_pack.beatmapsets.len()
+        );
+    }
+    println!("Packs missing beatmapsets ({}):", incomplete_packs.len());
+    for (catalogue_pack, missing) in incomplete_packs {
+        println!(
+            " - {}: {} of {} beatmapsets missing",
+            catalogue_pack.pack,
+            missing.len(),
+            catalogue_pack.beatmapsets.len()
+        );
+        for beatmapset in missing {
+            if library.contains(&beatmapset.id) {
+                println!("    - {} (found in another pack)", beatmapset);
+            } else {
+                println!("    - {}", beatmapset);
+            }
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use crate::error::Error;
+use std::collections::BTreeMap as Map;
 use std::convert::TryFrom;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -21,6 +23,42 @@ pub enum OsuBeatmapStatus {
     NotInstalled,
 }
 
+#[derive(Debug, Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Display)]
+pub enum OsuMode {
+    #[display(fmt = "osu!standard")]
+    Standard,
+    #[display(fmt = "osu!taiko")]
+    Taiko,
+    #[display(fmt = "osu!catch")]
+    Catch,
+    #[display(fmt = "osu!mania")]
+    Mania,
+}
+
+impl From<osu_db::Mode> for OsuMode {
+    fn from(mode: osu_db::Mode) -> Self {
+        match mode {
+            osu_db::Mode::Standard => OsuMode::Standard,
+            osu_db::Mode::Taiko => OsuMode::Taiko,
+            osu_db::Mode::CatchTheBeat => OsuMode::Catch,
+            osu_db::Mode::Mania => OsuMode::Mania,
+        }
+    }
+}
+
+impl std::str::FromStr for OsuMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "osu" | "std" | "standard" => Ok(OsuMode::Standard),
+            "taiko" => Ok(OsuMode::Taiko),
+            "catch" | "ctb" | "fruits" => Ok(OsuMode::Catch),
+            "mania" => Ok(OsuMode::Mania),
+            _ => Err(format!("{:?} is not one of osu, taiko, catch or mania", s)),
+        }
+    }
+}
+
 impl From<osu_db::listing::Grade> for OsuBeatmapStatus {
     fn from(grade: osu_db::listing::Grade) -> Self {
         match grade {
@@ -54,13 +92,19 @@ pub trait Osu {
     fn get_beatmap_grade_taiko(&self, beatmapset_id: u64, beatmap_id: u64) -> OsuBeatmapStatus;
     fn get_beatmap_grade_ctb(&self, beatmapset_id: u64, beatmap_id: u64) -> OsuBeatmapStatus;
     fn get_beatmap_grade_mania(&self, beatmapset_id: u64, beatmap_id: u64) -> OsuBeatmapStatus;
+    fn get_beatmap_mode(&self, beatmapset_id: u64, beatmap_id: u64) -> Option<OsuMode>;
+    fn get_beatmap_stars(&self, beatmapset_id: u64, beatmap_id: u64) -> Option<f64>;
+    fn get_beatmap_name(&self, beatmapset_id: u64, beatmap_id: u64) -> Option<String>;
+    fn get_beatmap_last_played(&self, beatmapset_id: u64, beatmap_id: u64) -> Option<String>;
+    fn get_songs_path(&self) -> Option<PathBuf>;
+    fn get_library(&self) -> Result<Map<u64, OsuBeatmapSetMetadata>, Error>;
 }
 #[derive(Debug, Clone, new)]
 pub struct Osu50HashResolver {
     pub folder: PathBuf,
 }
 impl Osu50HashResolver {
-    pub fn resolve(&self, hash: &str) -> Result<PathBuf, String> {
+    pub fn resolve(&self, hash: &str) -> Result<PathBuf, Error> {
         let final_path_buf = self
             .folder
             .join(hash.chars().take(1).collect::<String>())
@@ -69,7 +113,7 @@ impl Osu50HashResolver {
         if final_path_buf.is_file() {
             Ok(final_path_buf)
         } else {
-            Err(format!("{:?} is not a hashed file", final_path_buf))
+            Err(Error::MissingFile(final_path_buf, "osu!lazer"))
         }
     }
 }
@@ -144,6 +188,53 @@ impl Osu for Osu40 {
             .and_then(|x| Some(x.mania_grade))
             .into()
     }
+    fn get_beatmap_mode(&self, beatmapset_id: u64, beatmap_id: u64) -> Option<OsuMode> {
+        self.get_beatmap_(beatmapset_id, beatmap_id)
+            .and_then(|x| Some(x.mode.into()))
+    }
+    fn get_beatmap_stars(&self, beatmapset_id: u64, beatmap_id: u64) -> Option<f64> {
+        self.get_beatmap_(beatmapset_id, beatmap_id).and_then(|x| {
+            let ratings = match x.mode {
+                osu_db::Mode::Standard => &x.std_ratings,
+                osu_db::Mode::Taiko => &x.taiko_ratings,
+                osu_db::Mode::CatchTheBeat => &x.ctb_ratings,
+                osu_db::Mode::Mania => &x.mania_ratings,
+            };
+            ratings
+                .iter()
+                .find(|(mods, _)| mods.bits() == 0)
+                .and_then(|(_, stars)| Some(*stars))
+        })
+    }
+    fn get_beatmap_name(&self, beatmapset_id: u64, beatmap_id: u64) -> Option<String> {
+        self.get_beatmap_(beatmapset_id, beatmap_id)
+            .and_then(|x| x.difficulty_name.clone())
+    }
+    fn get_beatmap_last_played(&self, beatmapset_id: u64, beatmap_id: u64) -> Option<String> {
+        self.get_beatmap_(beatmapset_id, beatmap_id)
+            .and_then(|x| x.last_played)
+            .map(|last_played| last_played.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+    }
+    fn get_songs_path(&self) -> Option<PathBuf> {
+        Some((*self.songs_path).clone())
+    }
+    fn get_library(&self) -> Result<Map<u64, OsuBeatmapSetMetadata>, Error> {
+        let mut library = Map::new();
+        for beatmap in self.osu_db.beatmaps.iter().filter(|x| x.beatmapset_id > 0) {
+            library
+                .entry(beatmap.beatmapset_id as u64)
+                .or_insert_with(|| beatmapset_metadata(beatmap));
+        }
+        Ok(library)
+    }
+}
+fn beatmapset_metadata(beatmap: &osu_db::listing::Beatmap) -> OsuBeatmapSetMetadata {
+    OsuBeatmapSetMetadata::new(
+        beatmap.artist_ascii.clone().unwrap_or_default(),
+        beatmap.title_ascii.clone().unwrap_or_default(),
+        beatmap.creator.clone().unwrap_or_default(),
+        beatmap.folder_name.clone(),
+    )
 }
 impl Osu40 {
     fn get_beatmap_(
@@ -185,72 +276,66 @@ impl Osu for Osu50 {
     fn get_beatmap_grade_mania(&self, beatmapset_id: u64, beatmap_id: u64) -> OsuBeatmapStatus {
         OsuBeatmapStatus::NotInstalled
     }
+    fn get_beatmap_mode(&self, _beatmapset_id: u64, _beatmap_id: u64) -> Option<OsuMode> {
+        None
+    }
+    fn get_beatmap_stars(&self, _beatmapset_id: u64, _beatmap_id: u64) -> Option<f64> {
+        None
+    }
+    fn get_beatmap_name(&self, _beatmapset_id: u64, _beatmap_id: u64) -> Option<String> {
+        None
+    }
+    fn get_beatmap_last_played(&self, _beatmapset_id: u64, _beatmap_id: u64) -> Option<String> {
+        None
+    }
+    fn get_songs_path(&self) -> Option<PathBuf> {
+        None
+    }
+    fn get_library(&self) -> Result<Map<u64, OsuBeatmapSetMetadata>, Error> {
+        Err(Error::UnsupportedLibrary)
+    }
 }
 impl TryFrom<&PathBuf> for Osu40 {
-    type Error = String;
+    type Error = Error;
     fn try_from(path: &PathBuf) -> Result<Self, Self::Error> {
         if !path.is_dir() {
-            return Err(format!("{:?} is not a directory", path));
+            return Err(Error::NotADirectory(path.clone()));
         }
         let songs_path = path.join("Songs");
         if !songs_path.is_dir() {
-            return Err(format!(
-                "{:?} directory was not found in your osu!classic directory",
-                songs_path
-            ));
+            return Err(Error::MissingDirectory(songs_path, "osu!classic"));
         }
         let data_path = path.join("Data");
         if !data_path.is_dir() {
-            return Err(format!(
-                "{:?} directory was not found in your osu!classic directory",
-                data_path
-            ));
+            return Err(Error::MissingDirectory(data_path, "osu!classic"));
         }
         let replays_path = path.join("Replays");
         if !replays_path.is_dir() {
-            return Err(format!(
-                "{:?} directory was not found in your osu!classic directory",
-                replays_path
-            ));
+            return Err(Error::MissingDirectory(replays_path, "osu!classic"));
         }
         let osu_db_path = path.join("osu!.db");
         if !osu_db_path.is_file() {
-            return Err(format!(
-                "{:?} file was not found in your osu!classic directory",
-                osu_db_path
-            ));
+            return Err(Error::MissingFile(osu_db_path, "osu!classic"));
         }
         let clct_db_path = path.join("collection.db");
         if !clct_db_path.is_file() {
-            return Err(format!(
-                "{:?} file was not found in your osu!classic directory",
-                clct_db_path
-            ));
+            return Err(Error::MissingFile(clct_db_path, "osu!classic"));
         }
         let presence_db_path = path.join("presence.db");
         if !presence_db_path.is_file() {
-            return Err(format!(
-                "{:?} file was not found in your osu!classic directory",
-                presence_db_path
-            ));
+            return Err(Error::MissingFile(presence_db_path, "osu!classic"));
         }
         let scores_db_path = path.join("scores.db");
         if !scores_db_path.is_file() {
-            return Err(format!(
-                "{:?} file was not found in your osu!classic directory",
-                scores_db_path
-            ));
+            return Err(Error::MissingFile(scores_db_path, "osu!classic"));
         }
-        let osu_db = osu_db::Listing::from_file(&osu_db_path).map_err(|err| {
-            format!(
-                "{:?} file was deemed unreadable because {:?}",
-                osu_db_path, err
-            )
-        })?;
+        let osu_db = osu_db::Listing::from_file(&osu_db_path)
+            .map_err(|err| Error::UnreadableDatabase(osu_db_path.clone(), format!("{:?}", err)))?;
         let collection_db = osu_db::CollectionList::from_file(&clct_db_path)
-            .map_err(|err| format!("{:?} file was deemed unreadable {:?}", clct_db_path, err))?;
-        let scores_db = osu_db::ScoreList::from_file(&scores_db_path)
-            .map_err(|err| format!("{:?} file was deemed unreadable {:?}", scores_db_path, err))?;
+            .map_err(|err| Error::UnreadableDatabase(clct_db_path.clone(), format!("{:?}", err)))?;
+        let scores_db = osu_db::ScoreList::from_file(&scores_db_path).map_err(|err| {
+            Error::UnreadableDatabase(scores_db_path.clone(), format!("{:?}", err))
+        })?;
         Ok(Self::new(
             Arc::from(songs_path),
             Arc::from(data_path),
@@ -262,35 +347,31 @@ impl TryFrom<&PathBuf> for Osu40 {
     }
 }
 impl TryFrom<&PathBuf> for Osu50 {
-    type Error = String;
+    type Error = Error;
     fn try_from(path: &PathBuf) -> Result<Self, Self::Error> {
         if !path.is_dir() {
-            return Err(format!("{:?} is not a directory", path));
+            return Err(Error::NotADirectory(path.clone()));
         }
         let files_path = path.join("files");
         if !files_path.is_dir() {
-            return Err(format!(
-                "{:?} directory was not found in your osu!lazer directory",
-                files_path
-            ));
+            return Err(Error::MissingDirectory(files_path, "osu!lazer"));
         }
         let client_path = path.join("client.db");
         if !client_path.is_file() {
-            return Err(format!(
-                "{:?} file was not found in your osu!lazer directory",
-                files_path
-            ));
+            return Err(Error::MissingFile(client_path, "osu!lazer"));
         }
         let connection = rusqlite::Connection::open_with_flags(
-            client_path,
+            &client_path,
             rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
         )
-        .map_err(|x| format!("{:?}", x))?;
-        let mut connection_memory = rusqlite::Connection::open_in_memory().unwrap();
+        .map_err(|err| Error::UnreadableDatabase(client_path.clone(), err.to_string()))?;
+        let mut connection_memory = rusqlite::Connection::open_in_memory()
+            .map_err(|err| Error::UnreadableDatabase(client_path.clone(), err.to_string()))?;
         rusqlite::backup::Backup::new(&connection, &mut connection_memory)
-            .unwrap()
-            .run_to_completion(100000, std::time::Duration::from_millis(0), None)
-            .unwrap();
+            .and_then(|backup| {
+                backup.run_to_completion(100000, std::time::Duration::from_millis(0), None)
+            })
+            .map_err(|err| Error::UnreadableDatabase(client_path.clone(), err.to_string()))?;
         Ok(Self::new(
             Arc::new(Osu50HashResolver::new(files_path)),
             Arc::new(connection_memory),
@@ -298,6 +379,14 @@ impl TryFrom<&PathBuf> for Osu50 {
     }
 }
 
+#[derive(Debug, Clone, new)]
+pub struct OsuBeatmapSetMetadata {
+    pub artist: String,
+    pub title: String,
+    pub creator: String,
+    pub folder: Option<String>,
+}
+
 #[derive(Clone, new)]
 pub struct OsuBeatmapSet {
     osu: Arc<dyn Osu>,
@@ -305,6 +394,9 @@ pub struct OsuBeatmapSet {
 }
 
 impl OsuBeatmapSet {
+    pub fn beatmaps(&self) -> Vec<OsuBeatmap> {
+        self.osu.get_beatmapset_maps(self.bms_id)
+    }
     pub fn worst_rank(&self) -> OsuBeatmapStatus {
         let beatmaps = self.osu.get_beatmapset_maps(self.bms_id);
         if beatmaps.len() == 0 {
@@ -336,6 +428,17 @@ pub struct OsuBeatmap {
 }
 
 impl OsuBeatmap {
+    pub fn id(&self) -> u64 {
+        self.bm_id
+    }
+    pub fn grade(&self, mode: OsuMode) -> OsuBeatmapStatus {
+        match mode {
+            OsuMode::Standard => self.std_grade(),
+            OsuMode::Taiko => self.taiko_grade(),
+            OsuMode::Catch => self.ctb_grade(),
+            OsuMode::Mania => self.mania_grade(),
+        }
+    }
     fn std_grade(&self) -> OsuBeatmapStatus {
         self.osu.get_beatmap_grade_std(self.bms_id, self.bm_id)
     }
@@ -348,4 +451,29 @@ impl OsuBeatmap {
     fn mania_grade(&self) -> OsuBeatmapStatus {
         self.osu.get_beatmap_grade_mania(self.bms_id, self.bm_id)
     }
+    pub fn mode(&self) -> Option<OsuMode> {
+        self.osu.get_beatmap_mode(self.bms_id, self.bm_id)
+    }
+    pub fn stars(&self) -> Option<f64> {
+        self.osu.get_beatmap_stars(self.bms_id, self.bm_id)
+    }
+    pub fn name(&self) -> Option<String> {
+        self.osu.get_beatmap_name(self.bms_id, self.bm_id)
+    }
+    pub fn last_played(&self) -> Option<String> {
+        self.osu.get_beatmap_last_played(self.bms_id, self.bm_id)
+    }
+    pub fn status(&self) -> OsuBeatmapStatus {
+        let mut ranks: Vec<OsuBeatmapStatus> = vec![
+            self.std_grade(),
+            self.taiko_grade(),
+            self.ctb_grade(),
+            self.mania_grade(),
+        ]
+        .into_iter()
+        .filter(|g| ![OsuBeatmapStatus::NotPlayed, OsuBeatmapStatus::NotInstalled].contains(g))
+        .collect();
+        ranks.sort();
+        *ranks.last().unwrap_or(&OsuBeatmapStatus::NotPlayed)
+    }
 }
@@ -0,0 +1,317 @@
+use super::{io_error, Archive, ArchiveBackend, ArchiveEntry, Budget, Visit};
+use crate::error::Error;
+use std::collections::BTreeSet as Set;
+use std::ffi::{CStr, CString};
+use std::io::Read;
+use std::os::raw::{c_char, c_int};
+use std::path::PathBuf;
+
+// libarchive's C API is called directly, as the volumes of a set have to be
+// opened together and the entries walked one by one; build.rs finds it.
+#[allow(non_camel_case_types)]
+mod ffi {
+    use std::os::raw::{c_char, c_int, c_void};
+
+    pub enum archive {}
+    pub enum archive_entry {}
+
+    #[cfg(windows)]
+    pub type mode_t = u16;
+    #[cfg(not(windows))]
+    pub type mode_t = std::os::raw::c_uint;
+
+    pub const ARCHIVE_EOF: c_int = 1;
+    pub const ARCHIVE_OK: c_int = 0;
+    pub const ARCHIVE_WARN: c_int = -20;
+    pub const AE_IFMT: mode_t = 0o170000;
+    pub const AE_IFREG: mode_t = 0o100000;
+
+    #[link(name = "archive")]
+    extern "C" {
+        pub fn archive_read_new() -> *mut archive;
+        pub fn archive_read_support_filter_all(archive: *mut archive) -> c_int;
+        pub fn archive_read_support_format_all(archive: *mut archive) -> c_int;
+        pub fn archive_read_add_passphrase(
+            archive: *mut archive,
+            passphrase: *const c_char,
+        ) -> c_int;
+        pub fn archive_read_open_filenames(
+            archive: *mut archive,
+            filenames: *mut *const c_char,
+            block_size: usize,
+        ) -> c_int;
+        pub fn archive_read_next_header(
+            archive: *mut archive,
+            entry: *mut *mut archive_entry,
+        ) -> c_int;
+        pub fn archive_read_data(archive: *mut archive, buffer: *mut c_void, size: usize) -> isize;
+        pub fn archive_read_has_encrypted_entries(archive: *mut archive) -> c_int;
+        pub fn archive_errno(archive: *mut archive) -> c_int;
+        pub fn archive_error_string(archive: *mut archive) -> *const c_char;
+        pub fn archive_read_free(archive: *mut archive) -> c_int;
+        pub fn archive_entry_pathname(entry: *mut archive_entry) -> *const c_char;
+        pub fn archive_entry_pathname_utf8(entry: *mut archive_entry) -> *const c_char;
+        pub fn archive_entry_size(entry: *mut archive_entry) -> i64;
+        pub fn archive_entry_size_is_set(entry: *mut archive_entry) -> c_int;
+        pub fn archive_entry_filetype(entry: *mut archive_entry) -> mode_t;
+        pub fn archive_entry_is_encrypted(entry: *mut archive_entry) -> c_int;
+    }
+}
+
+const BLOCK_SIZE: usize = 64 * 1024;
+
+struct Header {
+    name: String,
+    size: Option<u64>,
+    is_file: bool,
+    encrypted: bool,
+}
+
+#[derive(new)]
+struct ReadError {
+    message: String,
+    encrypted: bool,
+    #[new(default)]
+    os_error: Option<c_int>,
+}
+
+struct Reader {
+    handle: *mut ffi::archive,
+    opened: bool,
+    encrypted: bool,
+}
+
+impl Reader {
+    fn open(volumes: &[PathBuf], password: Option<&str>) -> Result<Self, ReadError> {
+        let volumes = volumes
+            .iter()
+            .map(|volume| {
+                CString::new(volume.to_string_lossy().as_bytes())
+                    .map_err(|_| ReadError::new(format!("{:?} contains a NUL byte", volume), false))
+            })
+            .collect::<Result<Vec<CString>, ReadError>>()?;
+        let mut filenames: Vec<*const c_char> = volumes
+            .iter()
+            .map(|volume| volume.as_ptr())
+            .chain(std::iter::once(std::ptr::null()))
+            .collect();
+        let mut reader = Self {
+            handle: unsafe { ffi::archive_read_new() },
+            opened: false,
+            encrypted: false,
+        };
+        if reader.handle.is_null() {
+            return Err(ReadError::new(
+                "libarchive could not allocate a reader".to_string(),
+                false,
+            ));
+        }
+        unsafe {
+            ffi::archive_read_support_filter_all(reader.handle);
+            ffi::archive_read_support_format_all(reader.handle);
+            if let Some(password) = password {
+                let password = CString::new(password).map_err(|_| {
+                    ReadError::new("its password contains a NUL byte".to_string(), true)
+                })?;
+                ffi::archive_read_add_passphrase(reader.handle, password.as_ptr());
+            }
+            let status =
+                ffi::archive_read_open_filenames(reader.handle, filenames.as_mut_ptr(), BLOCK_SIZE);
+            reader.check(status)?;
+        }
+        reader.opened = true;
+        Ok(reader)
+    }
+
+    fn check(&self, status: c_int) -> Result<(), ReadError> {
+        if status == ffi::ARCHIVE_OK || status == ffi::ARCHIVE_WARN {
+            Ok(())
+        } else {
+            // libarchive crashes when asked about encryption before it has
+            // recognised the format.
+            let encrypted =
+                self.opened && unsafe { ffi::archive_read_has_encrypted_entries(self.handle) } > 0;
+            let mut err = ReadError::new(self.message(), encrypted);
+            err.os_error = self.os_error();
+            Err(err)
+        }
+    }
+
+    // libarchive reports bad content with its own error numbers, and the
+    // system's when reading the volumes failed.
+    fn os_error(&self) -> Option<c_int> {
+        Some(unsafe { ffi::archive_errno(self.handle) })
+            .filter(|errno| *errno > 0 && ![libc::EILSEQ, libc::EINVAL].contains(errno))
+    }
+
+    fn message(&self) -> String {
+        let message = unsafe { ffi::archive_error_string(self.handle) };
+        if message.is_null() {
+            "libarchive gave no reason".to_string()
+        } else {
+            unsafe { CStr::from_ptr(message) }
+                .to_string_lossy()
+                .into_owned()
+        }
+    }
+
+    fn next(&mut self) -> Result<Option<Header>, ReadError> {
+        let mut entry = std::ptr::null_mut();
+        let status = unsafe { ffi::archive_read_next_header(self.handle, &mut entry) };
+        if status == ffi::ARCHIVE_EOF {
+            return Ok(None);
+        }
+        self.check(status)?;
+        unsafe {
+            let mut name = ffi::archive_entry_pathname_utf8(entry);
+            if name.is_null() {
+                name = ffi::archive_entry_pathname(entry);
+            }
+            if name.is_null() {
+                return Err(ReadError::new("an entry has no name".to_string(), false));
+            }
+            self.encrypted = ffi::archive_entry_is_encrypted(entry) > 0;
+            Ok(Some(Header {
+                name: CStr::from_ptr(name).to_string_lossy().into_owned(),
+                size: Some(ffi::archive_entry_size(entry) as u64)
+                    .filter(|_| ffi::archive_entry_size_is_set(entry) != 0),
+                is_file: ffi::archive_entry_filetype(entry) & ffi::AE_IFMT == ffi::AE_IFREG,
+                encrypted: self.encrypted,
+            }))
+        }
+    }
+}
+
+impl Read for Reader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read =
+            unsafe { ffi::archive_read_data(self.handle, buf.as_mut_ptr().cast(), buf.len()) };
+        if read >= 0 {
+            Ok(read as usize)
+        } else if self.encrypted {
+            Err(std::io::Error::other(undecryptable(self.message())))
+        } else if let Some(errno) = self.os_error() {
+            Err(std::io::Error::from_raw_os_error(errno))
+        } else {
+            Err(std::io::Error::other(self.message()))
+        }
+    }
+}
+
+impl Drop for Reader {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::archive_read_free(self.handle);
+        }
+    }
+}
+
+pub struct LibarchiveBackend;
+
+impl ArchiveBackend for LibarchiveBackend {
+    fn supports(&self, _file_name: &str) -> bool {
+        true
+    }
+
+    fn list(
+        &self,
+        archive: &Archive,
+        password: Option<&str>,
+        budget: &Budget,
+    ) -> Result<Vec<ArchiveEntry>, Error> {
+        let other = |message| Error::UnlistableArchive(archive.path.clone(), message);
+        let mut entries = vec![];
+        for volumes in archive.segments() {
+            let mut reader = Reader::open(volumes, password)
+                .map_err(|err| error(archive, password, err, other))?;
+            while let Some(header) = reader
+                .next()
+                .map_err(|err| error(archive, password, err, other))?
+            {
+                budget.entry()?;
+                if header.encrypted && password.is_none() {
+                    return Err(Error::EncryptedArchive(archive.path.clone()));
+                }
+                spend(&mut reader, &header, budget).map_err(|err| io_error(archive, err, other))?;
+                if header.is_file {
+                    entries.push(ArchiveEntry::new(header.name, header.size, None));
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    fn extract(
+        &self,
+        archive: &Archive,
+        password: Option<&str>,
+        names: &Set<String>,
+        budget: &Budget,
+        visit: &mut Visit,
+    ) -> Result<(), Error> {
+        let other = |message| Error::Unextractable(archive.path.clone(), message);
+        let mut visited = Set::new();
+        for volumes in archive.segments() {
+            let mut reader = Reader::open(volumes, password)
+                .map_err(|err| error(archive, password, err, other))?;
+            while visited.len() < names.len() {
+                let header = match reader
+                    .next()
+                    .map_err(|err| error(archive, password, err, other))?
+                {
+                    Some(header) => header,
+                    None => break,
+                };
+                budget.entry()?;
+                if header.is_file
+                    && names.contains(&header.name)
+                    && visited.insert(header.name.clone())
+                {
+                    if header.encrypted && password.is_none() {
+                        return Err(Error::EncryptedArchive(archive.path.clone()));
+                    }
+                    visit(&header.name, Ok(&mut budget.reader(&mut reader)));
+                } else {
+                    spend(&mut reader, &header, budget).map_err(|err| other(err.to_string()))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Spends the budget on an entry that is not read: its declared size, or its
+// data when libarchive does not know the size until it has decompressed it.
+fn spend(reader: &mut Reader, header: &Header, budget: &Budget) -> std::io::Result<()> {
+    match header.size {
+        Some(size) => budget.consume(size).map_err(std::io::Error::other),
+        None => std::io::copy(&mut budget.reader(reader), &mut std::io::sink()).map(|_| ()),
+    }
+}
+
+fn error<F: Fn(String) -> Error>(
+    archive: &Archive,
+    password: Option<&str>,
+    err: ReadError,
+    other: F,
+) -> Error {
+    if let Some(errno) = err.os_error {
+        Error::Io(
+            archive.path.clone(),
+            std::io::Error::from_raw_os_error(errno),
+        )
+    } else if !err.encrypted {
+        other(err.message)
+    } else if password.is_some() {
+        other(undecryptable(err.message))
+    } else {
+        Error::EncryptedArchive(archive.path.clone())
+    }
+}
+
+fn undecryptable(message: String) -> String {
+    format!(
+        "libarchive could not decrypt it ({}), build with the sevenz or unrar feature for 7z and RAR",
+        message
+    )
+}
@@ -0,0 +1,132 @@
+#[cfg(feature = "libarchive")]
+mod libarchive_backend;
+#[cfg(feature = "sevenz")]
+mod sevenz_backend;
+#[cfg(feature = "tarball")]
+mod tar_backend;
+#[cfg(feature = "unrar")]
+mod unrar_backend;
+mod volume;
+mod zip_backend;
+
+pub use self::volume::{group_volumes, is_volume, Archive};
+use super::limits::Budget;
+use crate::error::Error;
+use std::collections::BTreeSet as Set;
+use std::io::Read;
+use std::path::Path;
+
+const ARCHIVE_SUFFIXES: [&str; 10] = [
+    ".zip", ".7z", ".rar", ".tar", ".tar.gz", ".tgz", ".tar.zst", ".tzst", ".tar.xz", ".txz",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, new)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub size: Option<u64>,
+    pub crc32: Option<u32>,
+}
+
+pub type Visit<'a> = dyn FnMut(&str, Result<&mut dyn Read, Error>) + 'a;
+
+pub trait ArchiveBackend: Sync {
+    fn supports(&self, file_name: &str) -> bool;
+    // Both spend `budget` on every entry and byte they go through, so that
+    // the limits hold however an archive lies about its contents.
+    fn list(
+        &self,
+        archive: &Archive,
+        password: Option<&str>,
+        budget: &Budget,
+    ) -> Result<Vec<ArchiveEntry>, Error>;
+    // Reads the archive once, handing every entry named in `names` to `visit`
+    // as it comes out.
+    fn extract(
+        &self,
+        archive: &Archive,
+        password: Option<&str>,
+        names: &Set<String>,
+        budget: &Budget,
+        visit: &mut Visit,
+    ) -> Result<(), Error>;
+}
+
+const BACKENDS: &[&dyn ArchiveBackend] = &[
+    &zip_backend::ZipBackend,
+    #[cfg(feature = "sevenz")]
+    &sevenz_backend::SevenZBackend,
+    #[cfg(feature = "tarball")]
+    &tar_backend::TarBackend,
+    #[cfg(feature = "unrar")]
+    &unrar_backend::UnrarBackend,
+    #[cfg(feature = "libarchive")]
+    &libarchive_backend::LibarchiveBackend,
+];
+
+fn file_name(archive: &Path) -> String {
+    archive
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("")
+        .to_lowercase()
+}
+
+pub fn is_known_archive(archive: &Path) -> bool {
+    let file_name = file_name(archive);
+    ARCHIVE_SUFFIXES
+        .iter()
+        .any(|suffix| file_name.ends_with(suffix))
+}
+
+pub fn strip_archive_suffix(name: &str) -> &str {
+    let lower = name.to_lowercase();
+    let suffix = ARCHIVE_SUFFIXES
+        .iter()
+        .filter(|suffix| lower.ends_with(*suffix))
+        .map(|suffix| suffix.len())
+        .max()
+        .unwrap_or(0);
+    &name[..name.len() - suffix]
+}
+
+pub fn backend_for(archive: &Path) -> Option<&'static dyn ArchiveBackend> {
+    let file_name = file_name(archive);
+    BACKENDS
+        .iter()
+        .find(|backend| backend.supports(&file_name))
+        .copied()
+}
+
+fn backend(archive: &Archive) -> Result<&'static dyn ArchiveBackend, Error> {
+    backend_for(&archive.path).ok_or_else(|| {
+        Error::UnlistableArchive(archive.path.clone(), "no backend compiled in".to_string())
+    })
+}
+
+// A file of the archive failing to read says nothing about the archive itself,
+// so it is told apart from the errors about its content, which are the only
+// ones the cache remembers.
+fn io_error<F: Fn(String) -> Error>(archive: &Archive, err: std::io::Error, other: F) -> Error {
+    match err.raw_os_error() {
+        Some(_) => Error::Io(archive.path.clone(), err),
+        None => other(err.to_string()),
+    }
+}
+
+pub fn list_archive(
+    archive: &Archive,
+    password: Option<&str>,
+    budget: &Budget,
+) -> Result<Vec<ArchiveEntry>, Error> {
+    backend(archive)?.list(archive, password, budget)
+}
+
+pub fn extract_entries(
+    archive: &Archive,
+    password: Option<&str>,
+    names: &Set<String>,
+    budget: &Budget,
+    visit: &mut Visit,
+) -> Result<(), Error> {
+    backend(archive)?.extract(archive, password, names, budget, visit)
+}
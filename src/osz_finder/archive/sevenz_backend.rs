@@ -0,0 +1,93 @@
+use super::volume::VolumeReader;
+use super::{io_error, Archive, ArchiveBackend, ArchiveEntry, Budget, Visit};
+use crate::error::Error;
+use std::collections::BTreeSet as Set;
+
+pub struct SevenZBackend;
+
+impl SevenZBackend {
+    fn open(
+        &self,
+        archive: &Archive,
+        password: Option<&str>,
+    ) -> Result<sevenz_rust::SevenZReader<VolumeReader>, Error> {
+        let volumes = archive.open()?;
+        let size = volumes.size();
+        let password = password
+            .map(sevenz_rust::Password::from)
+            .unwrap_or_else(sevenz_rust::Password::empty);
+        sevenz_rust::SevenZReader::new(volumes, size, password).map_err(|err| {
+            error(archive, err, |err| {
+                Error::UnlistableArchive(archive.path.clone(), err)
+            })
+        })
+    }
+}
+
+impl ArchiveBackend for SevenZBackend {
+    fn supports(&self, file_name: &str) -> bool {
+        file_name.ends_with(".7z")
+    }
+
+    fn list(
+        &self,
+        archive: &Archive,
+        password: Option<&str>,
+        budget: &Budget,
+    ) -> Result<Vec<ArchiveEntry>, Error> {
+        let reader = self.open(archive, password)?;
+        let mut entries = vec![];
+        for file in reader.archive().files.iter() {
+            budget.entry()?;
+            budget.consume(file.size())?;
+            if !file.is_directory() {
+                entries.push(ArchiveEntry::new(
+                    file.name().to_string(),
+                    Some(file.size()),
+                    Some(file.crc as u32).filter(|_| file.has_crc),
+                ));
+            }
+        }
+        Ok(entries)
+    }
+
+    fn extract(
+        &self,
+        archive: &Archive,
+        password: Option<&str>,
+        names: &Set<String>,
+        budget: &Budget,
+        visit: &mut Visit,
+    ) -> Result<(), Error> {
+        let mut reader = self.open(archive, password)?;
+        let mut visited = Set::new();
+        reader
+            .for_each_entries(|entry, data| {
+                budget.entry().map_err(std::io::Error::other)?;
+                let mut data = budget.reader(data);
+                if names.contains(entry.name()) && visited.insert(entry.name().to_string()) {
+                    visit(entry.name(), Ok(&mut data));
+                }
+                // Entries share solid blocks, so whatever was not read is
+                // skipped to keep the next one aligned.
+                std::io::copy(&mut data, &mut std::io::sink())?;
+                Ok(visited.len() < names.len())
+            })
+            .map_err(|err| {
+                error(archive, err, |err| {
+                    Error::Unextractable(archive.path.clone(), err)
+                })
+            })
+    }
+}
+
+fn error<F: Fn(String) -> Error>(archive: &Archive, err: sevenz_rust::Error, other: F) -> Error {
+    match err {
+        sevenz_rust::Error::PasswordRequired => Error::EncryptedArchive(archive.path.clone()),
+        sevenz_rust::Error::MaybeBadPassword(_) => Error::WrongPassword(archive.path.clone()),
+        sevenz_rust::Error::Io(err, _) | sevenz_rust::Error::FileOpen(err, _) => {
+            io_error(archive, err, other)
+        }
+        err => other(err.to_string()),
+    }
+}
@@ -0,0 +1,89 @@
+use super::{io_error, Archive, ArchiveBackend, ArchiveEntry, Budget, Visit};
+use crate::error::Error;
+use std::collections::BTreeSet as Set;
+use std::io::{BufReader, Read};
+
+const TAR_SUFFIXES: [&str; 7] = [
+    ".tar", ".tar.gz", ".tgz", ".tar.zst", ".tzst", ".tar.xz", ".txz",
+];
+
+pub struct TarBackend;
+
+impl TarBackend {
+    fn open(&self, archive: &Archive) -> Result<Box<dyn Read>, Error> {
+        let file = BufReader::new(archive.open()?);
+        let file_name = super::file_name(&archive.path);
+        Ok(
+            if file_name.ends_with(".gz") || file_name.ends_with(".tgz") {
+                Box::new(flate2::bufread::GzDecoder::new(file))
+            } else if file_name.ends_with(".zst") || file_name.ends_with(".tzst") {
+                Box::new(
+                    zstd::stream::read::Decoder::with_buffer(file)
+                        .map_err(|err| Error::Io(archive.path.clone(), err))?,
+                )
+            } else if file_name.ends_with(".xz") || file_name.ends_with(".txz") {
+                Box::new(xz2::bufread::XzDecoder::new(file))
+            } else {
+                Box::new(file)
+            },
+        )
+    }
+}
+
+impl ArchiveBackend for TarBackend {
+    fn supports(&self, file_name: &str) -> bool {
+        TAR_SUFFIXES
+            .iter()
+            .any(|suffix| file_name.ends_with(suffix))
+    }
+
+    fn list(
+        &self,
+        archive: &Archive,
+        _password: Option<&str>,
+        budget: &Budget,
+    ) -> Result<Vec<ArchiveEntry>, Error> {
+        let other = |message| Error::UnlistableArchive(archive.path.clone(), message);
+        let error = |err: std::io::Error| io_error(archive, err, other);
+        let mut tar = tar::Archive::new(budget.reader(self.open(archive)?));
+        let mut entries = vec![];
+        for entry in tar.entries().map_err(error)? {
+            let entry = entry.map_err(error)?;
+            budget.entry()?;
+            if entry.header().entry_type().is_file() {
+                let name = entry.path().map_err(error)?.to_string_lossy().to_string();
+                entries.push(ArchiveEntry::new(name, Some(entry.size()), None));
+            }
+        }
+        Ok(entries)
+    }
+
+    fn extract(
+        &self,
+        archive: &Archive,
+        _password: Option<&str>,
+        names: &Set<String>,
+        budget: &Budget,
+        visit: &mut Visit,
+    ) -> Result<(), Error> {
+        let error =
+            |err: std::io::Error| Error::Unextractable(archive.path.clone(), err.to_string());
+        let mut tar = tar::Archive::new(budget.reader(self.open(archive)?));
+        let mut visited = Set::new();
+        for entry in tar.entries().map_err(error)? {
+            if visited.len() == names.len() {
+                break;
+            }
+            let mut entry = entry.map_err(error)?;
+            budget.entry()?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let name = entry.path().map_err(error)?.to_string_lossy().to_string();
+            if names.contains(&name) && visited.insert(name.clone()) {
+                visit(&name, Ok(&mut entry));
+            }
+        }
+        Ok(())
+    }
+}
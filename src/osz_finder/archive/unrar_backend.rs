@@ -0,0 +1,138 @@
+use super::{Archive, ArchiveBackend, ArchiveEntry, Budget, Visit};
+use crate::error::Error;
+use crate::osz_finder::limits::relative_entry_path;
+use std::collections::BTreeSet as Set;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use unrar::error::{Code, UnrarError};
+
+static EXTRACTIONS: AtomicUsize = AtomicUsize::new(0);
+
+pub struct UnrarBackend;
+
+impl UnrarBackend {
+    fn open(&self, archive: &Archive, password: Option<&str>) -> Result<unrar::Archive<'_>, Error> {
+        let path = archive
+            .first_volume()
+            .to_str()
+            .ok_or_else(|| Error::NonUtf8Path(archive.first_volume().clone()))?;
+        Ok(match password {
+            Some(password) => unrar::Archive::with_password(path.to_string(), password.to_string()),
+            None => unrar::Archive::new(path.to_string()),
+        })
+    }
+
+    fn headers<F: Fn(String) -> Error + Copy>(
+        &self,
+        archive: &Archive,
+        password: Option<&str>,
+        budget: &Budget,
+        other: F,
+    ) -> Result<Vec<unrar::archive::Entry>, Error> {
+        let entries = self
+            .open(archive, password)?
+            .list()
+            .map_err(|err| error(archive, err, other))?
+            .process()
+            .map_err(|err| error(archive, err, other))?;
+        for entry in entries.iter() {
+            budget.entry()?;
+            budget.consume(entry.unpacked_size as u64)?;
+        }
+        Ok(entries)
+    }
+}
+
+impl ArchiveBackend for UnrarBackend {
+    fn supports(&self, file_name: &str) -> bool {
+        file_name.ends_with(".rar")
+    }
+
+    fn list(
+        &self,
+        archive: &Archive,
+        password: Option<&str>,
+        budget: &Budget,
+    ) -> Result<Vec<ArchiveEntry>, Error> {
+        let other = |message| Error::UnlistableArchive(archive.path.clone(), message);
+        let entries = self.headers(archive, password, budget, other)?;
+        Ok(entries
+            .into_iter()
+            .filter(|x| x.is_file())
+            .map(|x| ArchiveEntry::new(x.filename, Some(x.unpacked_size as u64), Some(x.file_crc)))
+            .collect())
+    }
+
+    fn extract(
+        &self,
+        archive: &Archive,
+        password: Option<&str>,
+        names: &Set<String>,
+        budget: &Budget,
+        visit: &mut Visit,
+    ) -> Result<(), Error> {
+        let other = |message| Error::Unextractable(archive.path.clone(), message);
+        // unrar extracts every entry to disk, so the budget is spent on their
+        // declared sizes before anything is written.
+        self.headers(archive, password, budget, other)?;
+        let directory = std::env::temp_dir().join(format!(
+            "{}-{}-{}",
+            env!("CARGO_PKG_NAME"),
+            std::process::id(),
+            EXTRACTIONS.fetch_add(1, Ordering::SeqCst)
+        ));
+        let directory_str = directory
+            .to_str()
+            .ok_or_else(|| Error::NonUtf8Path(directory.clone()))?
+            .to_string();
+        // unrar can only extract to disk, so every entry is removed as soon as
+        // it has been visited and the archive is still read only once.
+        let extracted = self
+            .open(archive, password)?
+            .extract_to(directory_str)
+            .map_err(|err| error(archive, err, other))
+            .and_then(|entries| {
+                let mut visited = Set::new();
+                for entry in entries {
+                    budget.check_deadline()?;
+                    let entry = entry.map_err(|err| error(archive, err, other))?;
+                    // Only regular files whose name stays inside the
+                    // temporary folder are ever opened or removed.
+                    let file = match relative_entry_path(&entry.filename) {
+                        Some(relative) if entry.is_file() => directory.join(relative),
+                        _ => continue,
+                    };
+                    let regular =
+                        std::fs::symlink_metadata(&file).is_ok_and(|metadata| metadata.is_file());
+                    if regular
+                        && names.contains(&entry.filename)
+                        && visited.insert(entry.filename.clone())
+                    {
+                        match std::fs::File::open(&file) {
+                            Ok(mut content) => visit(&entry.filename, Ok(&mut content)),
+                            Err(err) => visit(&entry.filename, Err(other(err.to_string()))),
+                        }
+                    }
+                    if regular {
+                        let _ = std::fs::remove_file(&file);
+                    }
+                    if visited.len() == names.len() {
+                        break;
+                    }
+                }
+                Ok(())
+            });
+        let _ = std::fs::remove_dir_all(&directory);
+        extracted
+    }
+}
+
+fn error<T, F: Fn(String) -> Error>(archive: &Archive, err: UnrarError<T>, other: F) -> Error {
+    match err.code {
+        Code::MissingPassword => Error::EncryptedArchive(archive.path.clone()),
+        Code::BadPassword => Error::WrongPassword(archive.path.clone()),
+        Code::EOpen | Code::ERead => {
+            Error::Io(archive.path.clone(), std::io::Error::other(err.to_string()))
+        }
+        _ => other(err.to_string()),
+    }
+}
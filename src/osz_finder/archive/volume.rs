@@ -0,0 +1,581 @@
+use crate::error::Error;
+use std::collections::BTreeMap as Map;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, new)]
+pub struct Archive {
+    pub path: PathBuf,
+    pub volumes: Vec<PathBuf>,
+}
+
+impl Archive {
+    pub fn single(path: &Path) -> Self {
+        Self::new(path.to_path_buf(), vec![path.to_path_buf()])
+    }
+
+    pub fn first_volume(&self) -> &PathBuf {
+        self.volumes.first().unwrap_or(&self.path)
+    }
+
+    // libarchive stops at the end block of a RAR volume unless a file goes on
+    // in the next one, so such sets are opened again from each volume starting
+    // with a whole file.
+    #[cfg(feature = "libarchive")]
+    pub fn segments(&self) -> Vec<&[PathBuf]> {
+        let mut segments = vec![];
+        let mut start = 0;
+        for index in 1..self.volumes.len() {
+            let starts_whole = File::open(&self.volumes[index]).is_ok_and(is_rar)
+                && File::open(&self.volumes[index - 1])
+                    .ok()
+                    .and_then(rar_end)
+                    .is_some_and(|end| !end.split);
+            if starts_whole {
+                segments.push(&self.volumes[start..index]);
+                start = index;
+            }
+        }
+        segments.push(&self.volumes[start..]);
+        segments
+    }
+
+    pub fn open(&self) -> Result<VolumeReader, Error> {
+        let mut files = vec![];
+        for volume in self.volumes.iter() {
+            let file = File::open(volume).map_err(|err| Error::Io(volume.clone(), err))?;
+            let len = file
+                .metadata()
+                .map_err(|err| Error::Io(volume.clone(), err))?
+                .len();
+            files.push((file, len));
+        }
+        Ok(VolumeReader::new(files))
+    }
+}
+
+impl AsRef<Path> for Archive {
+    fn as_ref(&self) -> &Path {
+        self.path.as_ref()
+    }
+}
+
+#[derive(Debug, new)]
+pub struct VolumeReader {
+    files: Vec<(File, u64)>,
+    #[new(default)]
+    position: u64,
+}
+
+impl VolumeReader {
+    pub fn size(&self) -> u64 {
+        self.files.iter().map(|(_, len)| len).sum()
+    }
+}
+
+impl Read for VolumeReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut start = 0;
+        for (file, len) in self.files.iter_mut() {
+            if self.position < start + *len {
+                let offset = self.position - start;
+                file.seek(SeekFrom::Start(offset))?;
+                let max = buf.len().min((*len - offset) as usize);
+                let read = file.read(&mut buf[..max])?;
+                self.position += read as u64;
+                return Ok(read);
+            }
+            start += *len;
+        }
+        Ok(0)
+    }
+}
+
+impl Seek for VolumeReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(x) => x as i128,
+            SeekFrom::End(x) => self.size() as i128 + x as i128,
+            SeekFrom::Current(x) => self.position as i128 + x as i128,
+        };
+        if position < 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "seek before the first volume",
+            ));
+        }
+        self.position = position as u64;
+        Ok(self.position)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum VolumeStyle {
+    Part(usize),
+    Numbered,
+    OldRar,
+}
+
+impl VolumeStyle {
+    fn first_index(&self) -> u32 {
+        match self {
+            VolumeStyle::Part(_) | VolumeStyle::Numbered => 1,
+            VolumeStyle::OldRar => 0,
+        }
+    }
+
+    fn volume_path(&self, logical: &Path, index: u32) -> PathBuf {
+        let name = logical
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("");
+        let stem = &name[..name.rfind('.').unwrap_or(name.len())];
+        logical.with_file_name(match self {
+            VolumeStyle::Part(width) => {
+                format!("{}.part{:0width$}.rar", stem, index, width = width)
+            }
+            VolumeStyle::Numbered => format!("{}.{:03}", name, index),
+            VolumeStyle::OldRar if index == 0 => name.to_string(),
+            VolumeStyle::OldRar => format!("{}.r{:02}", stem, index - 1),
+        })
+    }
+}
+
+fn parse_volume(path: &Path) -> Option<(PathBuf, VolumeStyle, u32)> {
+    let name = path.file_name()?.to_str()?;
+    let lower = name.to_lowercase();
+    let original = |prefix: &str| name[..name.len() - (lower.len() - prefix.len())].to_string();
+    let digits = |x: &str| !x.is_empty() && x.chars().all(|c| c.is_ascii_digit());
+    let (rest, number) = lower.rsplit_once('.')?;
+    if number == "rar" {
+        let (stem, part) = rest.rsplit_once(".part")?;
+        if digits(part) {
+            let logical = path.with_file_name(format!("{}.rar", original(stem)));
+            return Some((logical, VolumeStyle::Part(part.len()), part.parse().ok()?));
+        }
+    } else if number.len() == 3 && digits(number) && super::is_known_archive(Path::new(rest)) {
+        let logical = path.with_file_name(original(rest));
+        return Some((logical, VolumeStyle::Numbered, number.parse().ok()?));
+    } else if number.len() == 3 && number.starts_with('r') && digits(&number[1..]) {
+        let logical = path.with_file_name(format!("{}.rar", original(rest)));
+        return Some((
+            logical,
+            VolumeStyle::OldRar,
+            number[1..].parse::<u32>().ok()? + 1,
+        ));
+    }
+    None
+}
+
+pub fn is_volume(path: &Path) -> bool {
+    parse_volume(path).is_some()
+}
+
+pub fn group_volumes(paths: Vec<PathBuf>) -> (Vec<Archive>, Vec<Error>) {
+    let key = |path: &Path| path.to_string_lossy().to_lowercase();
+    let mut singles = vec![];
+    let mut sets: Map<(String, VolumeStyle), (PathBuf, Map<u32, PathBuf>)> = Map::new();
+    for path in paths {
+        match parse_volume(&path) {
+            Some((logical, style, index)) => {
+                sets.entry((key(&logical), style))
+                    .or_insert_with(|| (logical, Map::new()))
+                    .1
+                    .insert(index, path);
+            }
+            None => singles.push(path),
+        }
+    }
+    let mut archives = vec![];
+    let mut errors = vec![];
+    for path in singles {
+        match sets.get_mut(&(key(&path), VolumeStyle::OldRar)) {
+            Some((logical, volumes)) => {
+                *logical = path.clone();
+                volumes.insert(0, path);
+            }
+            None => archives.push(Archive::single(&path)),
+        }
+    }
+    for ((_, style), (logical, volumes)) in sets {
+        let last = volumes.keys().last().copied().unwrap_or(0);
+        let missing = (style.first_index()..=last).find(|index| !volumes.contains_key(index));
+        if let Some(index) = missing {
+            errors.push(Error::MissingVolume(
+                logical.clone(),
+                style.volume_path(&logical, index),
+            ));
+            continue;
+        }
+        let archive = Archive::new(logical.clone(), volumes.into_values().collect());
+        if needs_next_volume(style, &archive) {
+            errors.push(Error::MissingVolume(
+                logical.clone(),
+                style.volume_path(&logical, last + 1),
+            ));
+        } else {
+            archives.push(archive);
+        }
+    }
+    (archives, errors)
+}
+
+fn needs_next_volume(style: VolumeStyle, archive: &Archive) -> bool {
+    match style {
+        VolumeStyle::Numbered => archive
+            .open()
+            .ok()
+            .and_then(|mut reader| {
+                let size = reader.size();
+                seven_zip_size(&mut reader)
+                    .map(|needed| needed > size)
+                    .or_else(|| rar_end(&mut reader).map(|end| end.next_volume || end.split))
+            })
+            .unwrap_or(false),
+        VolumeStyle::Part(_) | VolumeStyle::OldRar => archive
+            .volumes
+            .last()
+            .and_then(|volume| File::open(volume).ok())
+            .and_then(rar_end)
+            .map(|end| end.next_volume || end.split)
+            .unwrap_or(false),
+    }
+}
+
+fn seven_zip_size<R: Read + Seek>(reader: &mut R) -> Option<u64> {
+    let mut header = [0u8; 32];
+    reader.seek(SeekFrom::Start(0)).ok()?;
+    reader.read_exact(&mut header).ok()?;
+    if header[..6] != [b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C] {
+        return None;
+    }
+    let mut number = [0u8; 8];
+    number.copy_from_slice(&header[12..20]);
+    let next_header_offset = u64::from_le_bytes(number);
+    number.copy_from_slice(&header[20..28]);
+    let next_header_size = u64::from_le_bytes(number);
+    Some(32 + next_header_offset + next_header_size)
+}
+
+const RAR4_SIGNATURE: &[u8] = b"Rar!\x1a\x07\x00";
+const RAR5_SIGNATURE: &[u8] = b"Rar!\x1a\x07\x01\x00";
+
+#[derive(Debug, PartialEq, Eq, new)]
+struct RarEnd {
+    next_volume: bool,
+    split: bool,
+}
+
+// Walks the block headers of a RAR volume, skipping the packed data, to find
+// whether its end block announces another volume and whether its last file
+// (or a block running past the end of the data) goes on in the next one.
+fn rar_end<R: Read + Seek>(mut reader: R) -> Option<RarEnd> {
+    let end = reader.seek(SeekFrom::End(0)).ok()?;
+    let mut signature = [0u8; 8];
+    reader.seek(SeekFrom::Start(0)).ok()?;
+    reader.read_exact(&mut signature).ok()?;
+    if signature.starts_with(RAR4_SIGNATURE) {
+        rar4_end(&mut reader, end)
+    } else if signature.starts_with(RAR5_SIGNATURE) {
+        rar5_end(&mut reader, end)
+    } else {
+        None
+    }
+}
+
+#[cfg(feature = "libarchive")]
+fn is_rar<R: Read>(mut reader: R) -> bool {
+    let mut signature = [0u8; 8];
+    read_up_to(&mut reader, &mut signature).is_some()
+        && (signature.starts_with(RAR4_SIGNATURE) || signature.starts_with(RAR5_SIGNATURE))
+}
+
+fn rar4_end<R: Read + Seek>(reader: &mut R, end: u64) -> Option<RarEnd> {
+    let truncated = Some(RarEnd::new(false, true));
+    let mut position = RAR4_SIGNATURE.len() as u64;
+    let mut split = false;
+    while position < end {
+        reader.seek(SeekFrom::Start(position)).ok()?;
+        let mut header = [0u8; 11];
+        let read = read_up_to(reader, &mut header)?;
+        if read < 7 {
+            return truncated;
+        }
+        let kind = header[2];
+        let flags = u16::from_le_bytes([header[3], header[4]]);
+        let size = u16::from_le_bytes([header[5], header[6]]) as u64;
+        let mut data = 0;
+        if flags & 0x8000 != 0 {
+            if read < 11 {
+                return truncated;
+            }
+            data = u32::from_le_bytes([header[7], header[8], header[9], header[10]]) as u64;
+            if kind == 0x74 && flags & 0x0100 != 0 {
+                let mut high = [0u8; 4];
+                reader.seek(SeekFrom::Start(position + 32)).ok()?;
+                if read_up_to(reader, &mut high)? < 4 {
+                    return truncated;
+                }
+                data |= (u32::from_le_bytes(high) as u64) << 32;
+            }
+        }
+        match kind {
+            0x74 => split = flags & 0x0002 != 0,
+            0x7B => return Some(RarEnd::new(flags & 0x0001 != 0, split)),
+            _ => {}
+        }
+        if size < 7 {
+            return None;
+        }
+        position += size + data;
+    }
+    Some(RarEnd::new(false, split || position > end))
+}
+
+fn rar5_end<R: Read + Seek>(reader: &mut R, end: u64) -> Option<RarEnd> {
+    let truncated = Some(RarEnd::new(false, true));
+    let mut position = RAR5_SIGNATURE.len() as u64;
+    let mut split = false;
+    while position < end {
+        reader.seek(SeekFrom::Start(position + 4)).ok()?;
+        let mut prefix = [0u8; 3];
+        let read = read_up_to(reader, &mut prefix)?;
+        let (size, length) = match vint(&prefix[..read]) {
+            Some(vint) => vint,
+            None => return truncated,
+        };
+        if size > 2 * 1024 * 1024 {
+            return None;
+        }
+        let mut header = vec![0u8; size as usize];
+        reader
+            .seek(SeekFrom::Start(position + 4 + length as u64))
+            .ok()?;
+        if read_up_to(reader, &mut header)? < header.len() {
+            return truncated;
+        }
+        let mut fields = &header[..];
+        let mut next = || {
+            let (value, length) = vint(fields)?;
+            fields = &fields[length..];
+            Some(value)
+        };
+        let kind = next()?;
+        let flags = next()?;
+        if flags & 0x0001 != 0 {
+            next()?;
+        }
+        let data = if flags & 0x0002 != 0 { next()? } else { 0 };
+        match kind {
+            2 | 3 => split = flags & 0x0010 != 0,
+            4 => return None,
+            5 => return Some(RarEnd::new(next()? & 0x0001 != 0, split)),
+            _ => {}
+        }
+        position += 4 + length as u64 + size + data;
+    }
+    Some(RarEnd::new(false, split || position > end))
+}
+
+fn vint(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (index, byte) in bytes.iter().enumerate().take(10) {
+        value |= ((byte & 0x7F) as u64) << (7 * index);
+        if byte & 0x80 == 0 {
+            return Some((value, index + 1));
+        }
+    }
+    None
+}
+
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> Option<usize> {
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]).ok()? {
+            0 => break,
+            n => read += n,
+        }
+    }
+    Some(read)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(names: &[&str]) -> Vec<PathBuf> {
+        names
+            .iter()
+            .map(|name| Path::new("packs").join(name))
+            .collect()
+    }
+
+    fn volume(name: &str) -> Option<(PathBuf, VolumeStyle, u32)> {
+        parse_volume(&Path::new("packs").join(name))
+    }
+
+    #[test]
+    fn parses_part_volumes() {
+        assert_eq!(
+            volume("Pack.part01.rar"),
+            Some((Path::new("packs/Pack.rar").into(), VolumeStyle::Part(2), 1))
+        );
+        assert_eq!(
+            volume("pack.PART3.RAR"),
+            Some((Path::new("packs/pack.rar").into(), VolumeStyle::Part(1), 3))
+        );
+        assert_eq!(volume("pack.part.rar"), None);
+        assert_eq!(volume("pack.rar"), None);
+    }
+
+    #[test]
+    fn parses_numbered_volumes() {
+        assert_eq!(
+            volume("Pack.7z.001"),
+            Some((Path::new("packs/Pack.7z").into(), VolumeStyle::Numbered, 1))
+        );
+        assert_eq!(
+            volume("pack.tar.gz.012"),
+            Some((
+                Path::new("packs/pack.tar.gz").into(),
+                VolumeStyle::Numbered,
+                12
+            ))
+        );
+        assert_eq!(volume("track.mp3.001"), None);
+        assert_eq!(volume("pack.7z.01"), None);
+    }
+
+    #[test]
+    fn parses_old_rar_volumes() {
+        assert_eq!(
+            volume("Pack.r00"),
+            Some((Path::new("packs/Pack.rar").into(), VolumeStyle::OldRar, 1))
+        );
+        assert_eq!(
+            volume("pack.R12"),
+            Some((Path::new("packs/pack.rar").into(), VolumeStyle::OldRar, 13))
+        );
+        assert_eq!(volume("pack.r1"), None);
+        assert_eq!(volume("pack.rx0"), None);
+    }
+
+    #[test]
+    fn groups_volumes_and_keeps_singles() {
+        let (archives, errors) = group_volumes(paths(&[
+            "a.part2.rar",
+            "a.part1.rar",
+            "b.zip",
+            "c.7z.001",
+            "c.7z.002",
+        ]));
+        assert!(errors.is_empty());
+        assert_eq!(
+            archives,
+            vec![
+                Archive::single(Path::new("packs/b.zip")),
+                Archive::new("packs/a.rar".into(), paths(&["a.part1.rar", "a.part2.rar"])),
+                Archive::new("packs/c.7z".into(), paths(&["c.7z.001", "c.7z.002"])),
+            ]
+        );
+    }
+
+    #[test]
+    fn groups_old_rar_volumes_whatever_their_case() {
+        let (archives, errors) = group_volumes(paths(&["pack.r01", "Pack.RAR", "pack.r00"]));
+        assert!(errors.is_empty());
+        assert_eq!(
+            archives,
+            vec![Archive::new(
+                "packs/Pack.RAR".into(),
+                paths(&["Pack.RAR", "pack.r00", "pack.r01"])
+            )]
+        );
+    }
+
+    #[test]
+    fn reports_missing_volumes() {
+        let (archives, errors) = group_volumes(paths(&["a.part1.rar", "a.part3.rar", "b.r00"]));
+        assert!(archives.is_empty());
+        let missing: Vec<String> = errors
+            .iter()
+            .map(|err| match err {
+                Error::MissingVolume(_, volume) => volume.display().to_string(),
+                err => panic!("unexpected error {}", err),
+            })
+            .collect();
+        assert_eq!(missing, vec!["packs/a.part2.rar", "packs/b.rar"]);
+    }
+
+    fn rar4_block(kind: u8, flags: u16, data: &[u8]) -> Vec<u8> {
+        let size: u16 = if flags & 0x8000 != 0 { 11 } else { 7 };
+        let mut block = vec![0, 0, kind];
+        block.extend_from_slice(&flags.to_le_bytes());
+        block.extend_from_slice(&size.to_le_bytes());
+        if flags & 0x8000 != 0 {
+            block.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        }
+        block.extend_from_slice(data);
+        block
+    }
+
+    fn rar4(blocks: &[Vec<u8>]) -> std::io::Cursor<Vec<u8>> {
+        let mut volume = RAR4_SIGNATURE.to_vec();
+        volume.extend(rar4_block(0x73, 0x0001, &[]));
+        for block in blocks {
+            volume.extend_from_slice(block);
+        }
+        std::io::Cursor::new(volume)
+    }
+
+    #[test]
+    fn finds_how_rar4_volumes_end() {
+        let last = rar4(&[rar4_block(0x74, 0x8000, b"data"), rar4_block(0x7B, 0, &[])]);
+        assert_eq!(rar_end(last), Some(RarEnd::new(false, false)));
+        let middle = rar4(&[rar4_block(0x74, 0x8000, b"data"), rar4_block(0x7B, 1, &[])]);
+        assert_eq!(rar_end(middle), Some(RarEnd::new(true, false)));
+        let split = rar4(&[rar4_block(0x74, 0x8002, b"data"), rar4_block(0x7B, 1, &[])]);
+        assert_eq!(rar_end(split), Some(RarEnd::new(true, true)));
+        let mut truncated = rar4(&[rar4_block(0x74, 0x8000, b"data")]).into_inner();
+        truncated.truncate(truncated.len() - 2);
+        assert_eq!(
+            rar_end(std::io::Cursor::new(truncated)),
+            Some(RarEnd::new(false, true))
+        );
+        assert_eq!(rar_end(std::io::Cursor::new(b"PK\x03\x04".to_vec())), None);
+    }
+
+    #[test]
+    fn finds_how_rar5_volumes_end() {
+        let block = |kind: u8, flags: u8, extra: &[u8]| {
+            let mut header = vec![kind, flags];
+            header.extend_from_slice(extra);
+            let mut block = vec![0, 0, 0, 0, header.len() as u8];
+            block.extend(header);
+            block
+        };
+        let mut volume = RAR5_SIGNATURE.to_vec();
+        volume.extend(block(1, 0, &[1]));
+        volume.extend(block(2, 0x02, &[4]));
+        volume.extend_from_slice(b"data");
+        let mut last = volume.clone();
+        last.extend(block(5, 0, &[0]));
+        assert_eq!(
+            rar_end(std::io::Cursor::new(last)),
+            Some(RarEnd::new(false, false))
+        );
+        volume.extend(block(5, 0, &[1]));
+        assert_eq!(
+            rar_end(std::io::Cursor::new(volume)),
+            Some(RarEnd::new(true, false))
+        );
+    }
+
+    #[test]
+    fn reads_vints() {
+        assert_eq!(vint(&[0x05]), Some((5, 1)));
+        assert_eq!(vint(&[0x80 | 0x01, 0x02]), Some((0x101, 2)));
+        assert_eq!(vint(&[0x80]), None);
+    }
+}
@@ -0,0 +1,91 @@
+use super::{io_error, Archive, ArchiveBackend, ArchiveEntry, Budget, Visit};
+use crate::error::Error;
+use std::collections::BTreeSet as Set;
+use zip::result::ZipError;
+
+pub struct ZipBackend;
+
+impl ArchiveBackend for ZipBackend {
+    fn supports(&self, file_name: &str) -> bool {
+        file_name.ends_with(".zip")
+    }
+
+    fn list(
+        &self,
+        archive: &Archive,
+        password: Option<&str>,
+        budget: &Budget,
+    ) -> Result<Vec<ArchiveEntry>, Error> {
+        let other = |message| Error::UnlistableArchive(archive.path.clone(), message);
+        let error = |err: ZipError| match err {
+            ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED) => {
+                Error::EncryptedArchive(archive.path.clone())
+            }
+            ZipError::Io(err) => io_error(archive, err, other),
+            err => other(err.to_string()),
+        };
+        let mut zip = zip::ZipArchive::new(archive.open()?).map_err(error)?;
+        for index in 0..zip.len() {
+            match password {
+                Some(password) => {
+                    zip.by_index_decrypt(index, password.as_bytes())
+                        .map_err(error)?
+                        .map_err(|_| Error::WrongPassword(archive.path.clone()))?;
+                }
+                None => {
+                    zip.by_index(index).map_err(error)?;
+                }
+            }
+        }
+        let mut entries = vec![];
+        for index in 0..zip.len() {
+            let file = zip.by_index_raw(index).map_err(error)?;
+            budget.entry()?;
+            budget.consume(file.size())?;
+            if file.is_file() {
+                entries.push(ArchiveEntry::new(
+                    file.name().to_string(),
+                    Some(file.size()),
+                    Some(file.crc32()),
+                ));
+            }
+        }
+        Ok(entries)
+    }
+
+    fn extract(
+        &self,
+        archive: &Archive,
+        password: Option<&str>,
+        names: &Set<String>,
+        budget: &Budget,
+        visit: &mut Visit,
+    ) -> Result<(), Error> {
+        let mut zip = zip::ZipArchive::new(archive.open()?)
+            .map_err(|err| Error::Unextractable(archive.path.clone(), err.to_string()))?;
+        for name in names {
+            budget.entry()?;
+            let error = |err: ZipError| match err {
+                ZipError::FileNotFound => {
+                    Error::MissingEntry(archive.path.clone(), name.to_string())
+                }
+                ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED) => {
+                    Error::EncryptedArchive(archive.path.clone())
+                }
+                err => Error::Unextractable(archive.path.join(name), err.to_string()),
+            };
+            let file = match password {
+                Some(password) => zip
+                    .by_name_decrypt(name, password.as_bytes())
+                    .map_err(error)
+                    .and_then(|file| file.map_err(|_| Error::WrongPassword(archive.path.clone()))),
+                None => zip.by_name(name).map_err(error),
+            };
+            match file {
+                Ok(file) => visit(name, Ok(&mut budget.reader(file))),
+                Err(err) => visit(name, Err(err)),
+            }
+        }
+        Ok(())
+    }
+}
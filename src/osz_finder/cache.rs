@@ -0,0 +1,222 @@
+use super::archive::{Archive, ArchiveEntry};
+use crate::error::Error;
+use rusqlite::{params, OptionalExtension};
+use std::path::{Path, PathBuf};
+
+const CACHE_VERSION: i64 = 4;
+
+pub struct ArchiveCache {
+    path: PathBuf,
+    connection: rusqlite::Connection,
+}
+
+impl ArchiveCache {
+    pub fn default_path() -> Option<PathBuf> {
+        std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+            .map(|cache_dir| {
+                cache_dir
+                    .join(env!("CARGO_PKG_NAME"))
+                    .join("archives.sqlite3")
+            })
+    }
+
+    pub fn open(path: &PathBuf) -> Result<Self, Error> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|err| Error::Io(parent.to_path_buf(), err))?;
+        }
+        let connection = rusqlite::Connection::open(path).map_err(|err| Self::error(path, err))?;
+        let cache = Self {
+            path: path.clone(),
+            connection,
+        };
+        let version: i64 = cache
+            .connection
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .map_err(|err| Self::error(path, err))?;
+        if version != CACHE_VERSION {
+            cache.clear()?;
+        }
+        Ok(cache)
+    }
+
+    pub fn clear(&self) -> Result<(), Error> {
+        self.connection
+            .execute_batch(&format!(
+                "DROP TABLE IF EXISTS archive_entry;
+                DROP TABLE IF EXISTS archive;
+                CREATE TABLE archive (
+                    id INTEGER PRIMARY KEY,
+                    path TEXT NOT NULL UNIQUE,
+                    volume TEXT NOT NULL,
+                    size INTEGER NOT NULL,
+                    mtime INTEGER NOT NULL,
+                    error TEXT
+                );
+                CREATE TABLE archive_entry (
+                    archive_id INTEGER NOT NULL REFERENCES archive(id),
+                    name TEXT NOT NULL,
+                    size INTEGER,
+                    crc32 INTEGER
+                );
+                CREATE INDEX archive_entry_archive_id ON archive_entry(archive_id);
+                PRAGMA user_version = {};",
+                CACHE_VERSION
+            ))
+            .map_err(|err| Self::error(&self.path, err))
+    }
+
+    pub fn get(&self, archive: &Archive) -> Option<Result<Vec<ArchiveEntry>, Error>> {
+        let (size, mtime) = stamp(&archive.volumes)?;
+        let (archive_id, error): (i64, Option<String>) = self
+            .connection
+            .query_row(
+                "SELECT id, error FROM archive WHERE path = ?1 AND size = ?2 AND mtime = ?3",
+                params![archive.path.to_str()?, size, mtime],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .ok()??;
+        if let Some(error) = error {
+            return Some(Err(Error::UnlistableArchive(archive.path.clone(), error)));
+        }
+        let mut statement = self
+            .connection
+            .prepare_cached("SELECT name, size, crc32 FROM archive_entry WHERE archive_id = ?1")
+            .ok()?;
+        let entries = statement
+            .query_map(params![archive_id], |row| {
+                Ok(ArchiveEntry::new(
+                    row.get(0)?,
+                    row.get::<_, Option<i64>>(1)?.map(|size| size as u64),
+                    row.get::<_, Option<i64>>(2)?.map(|crc32| crc32 as u32),
+                ))
+            })
+            .ok()?
+            .collect::<Result<Vec<ArchiveEntry>, rusqlite::Error>>()
+            .ok()?;
+        Some(Ok(entries))
+    }
+
+    pub fn put(
+        &mut self,
+        archive: &Archive,
+        password: Option<&str>,
+        listing: &Result<Vec<ArchiveEntry>, Error>,
+    ) -> Result<(), Error> {
+        // Only errors about the content are remembered: reading the files
+        // failing is an Error::Io, and an archive read with a password may
+        // list once the password is fixed.
+        let (entries, error) = match listing {
+            Ok(entries) => (entries.as_slice(), None),
+            Err(Error::UnlistableArchive(_, reason)) if password.is_none() => {
+                (&[][..], Some(reason))
+            }
+            Err(_) => return Ok(()),
+        };
+        let (size, mtime) = match stamp(&archive.volumes) {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+        let path = archive
+            .path
+            .to_str()
+            .ok_or_else(|| Error::NonUtf8Path(archive.path.clone()))?;
+        let volume = archive
+            .first_volume()
+            .to_str()
+            .ok_or_else(|| Error::NonUtf8Path(archive.first_volume().clone()))?;
+        let cache_path = self.path.clone();
+        let transaction = self
+            .connection
+            .transaction()
+            .map_err(|err| Self::error(&cache_path, err))?;
+        transaction
+            .execute(
+                "DELETE FROM archive_entry WHERE archive_id IN (SELECT id FROM archive WHERE path = ?1)",
+                params![path],
+            )
+            .and_then(|_| {
+                transaction.execute(
+                    "INSERT OR REPLACE INTO archive (path, volume, size, mtime, error)
+                    VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![path, volume, size, mtime, error],
+                )
+            })
+            .map_err(|err| Self::error(&cache_path, err))?;
+        let archive_id = transaction.last_insert_rowid();
+        for entry in entries {
+            transaction
+                .execute(
+                    "INSERT INTO archive_entry (archive_id, name, size, crc32) VALUES (?1, ?2, ?3, ?4)",
+                    params![
+                        archive_id,
+                        entry.name,
+                        entry.size.map(|size| size as i64),
+                        entry.crc32.map(|crc32| crc32 as i64)
+                    ],
+                )
+                .map_err(|err| Self::error(&cache_path, err))?;
+        }
+        transaction
+            .commit()
+            .map_err(|err| Self::error(&cache_path, err))
+    }
+
+    pub fn prune(&mut self, root: &Path) -> Result<(), Error> {
+        let cache_path = self.path.clone();
+        let transaction = self
+            .connection
+            .transaction()
+            .map_err(|err| Self::error(&cache_path, err))?;
+        let stale = transaction
+            .prepare("SELECT id, volume FROM archive")
+            .and_then(|mut statement| {
+                statement
+                    .query_map(params![], |row| {
+                        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+                    })?
+                    .collect::<Result<Vec<(i64, String)>, rusqlite::Error>>()
+            })
+            .map_err(|err| Self::error(&cache_path, err))?
+            .into_iter()
+            .filter(|(_, volume)| {
+                Path::new(volume).starts_with(root) && !Path::new(volume).exists()
+            });
+        for (archive_id, _) in stale {
+            transaction
+                .execute(
+                    "DELETE FROM archive_entry WHERE archive_id = ?1",
+                    params![archive_id],
+                )
+                .and_then(|_| {
+                    transaction.execute("DELETE FROM archive WHERE id = ?1", params![archive_id])
+                })
+                .map_err(|err| Self::error(&cache_path, err))?;
+        }
+        transaction
+            .commit()
+            .map_err(|err| Self::error(&cache_path, err))
+    }
+
+    fn error(path: &Path, err: rusqlite::Error) -> Error {
+        Error::Cache(path.to_path_buf(), err.to_string())
+    }
+}
+
+fn stamp(volumes: &[PathBuf]) -> Option<(i64, i64)> {
+    let mut size = 0;
+    let mut mtime = 0;
+    for volume in volumes {
+        let metadata = std::fs::metadata(volume).ok()?;
+        let modified = metadata
+            .modified()
+            .ok()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?;
+        size += metadata.len() as i64;
+        mtime = mtime.max(modified.as_nanos() as i64);
+    }
+    Some((size, mtime))
+}
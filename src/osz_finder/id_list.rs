@@ -0,0 +1,128 @@
+use crate::error::Error;
+use regex::Regex;
+use std::path::Path;
+use std::sync::OnceLock;
+
+const ID_LIST_EXTENSIONS: [&str; 3] = ["txt", "csv", "tsv"];
+const ID_COLUMNS: [&str; 7] = [
+    "beatmapset_id",
+    "beatmapsetid",
+    "beatmapset",
+    "set_id",
+    "id",
+    "url",
+    "link",
+];
+
+#[derive(Debug, Default, new)]
+pub struct IdList {
+    pub ids: Vec<u64>,
+    pub errors: Vec<Error>,
+}
+
+pub fn is_id_list(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ID_LIST_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+// A file given as a packs source is always read as a list and its bad lines
+// are reported. One found while walking a folder is only taken for a list
+// when it looks like one: every line of a '.txt' is an id or a link, or a
+// '.csv'/'.tsv' has an id column; anything else is some other text file.
+pub fn read_id_list(path: &Path, given: bool) -> Result<Option<IdList>, Error> {
+    let content = std::fs::read(path).map_err(|err| Error::Io(path.to_path_buf(), err))?;
+    let content = String::from_utf8_lossy(&content);
+    let list = match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("txt") => match read_lines(path, &content) {
+            list if !given && (list.ids.is_empty() || !list.errors.is_empty()) => return Ok(None),
+            list => list,
+        },
+        Some(ext) => match read_table(path, &content, ext.eq_ignore_ascii_case("tsv"))? {
+            Some(list) => list,
+            None if given => {
+                return Err(Error::IdList(
+                    path.to_path_buf(),
+                    "it has no beatmapset id column".to_string(),
+                ))
+            }
+            None => return Ok(None),
+        },
+        None => return Ok(None),
+    };
+    let mut seen = std::collections::BTreeSet::new();
+    let ids = list.ids.into_iter().filter(|id| seen.insert(*id)).collect();
+    Ok(Some(IdList::new(ids, list.errors)))
+}
+
+fn url() -> &'static Regex {
+    static URL: OnceLock<Regex> = OnceLock::new();
+    URL.get_or_init(|| {
+        Regex::new(r"osu\.ppy\.sh/(?:beatmapsets|s)/(\d+)").expect("url pattern is valid")
+    })
+}
+
+fn parse_id(value: &str) -> Option<u64> {
+    let value = value.trim();
+    value.parse::<u64>().ok().or_else(|| {
+        url()
+            .captures(value)
+            .and_then(|captures| captures.get(1))
+            .and_then(|id| id.as_str().parse::<u64>().ok())
+    })
+}
+
+fn read_lines(path: &Path, content: &str) -> IdList {
+    let mut list = IdList::default();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match parse_id(line.split_whitespace().next().unwrap_or("")) {
+            Some(id) => list.ids.push(id),
+            None => list
+                .errors
+                .push(Error::MalformedIdList(path.to_path_buf(), index + 1)),
+        }
+    }
+    list
+}
+
+fn read_table(path: &Path, content: &str, tabs: bool) -> Result<Option<IdList>, Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(if tabs { b'\t' } else { b',' })
+        .flexible(true)
+        .from_reader(content.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|err| Error::IdList(path.to_path_buf(), err.to_string()))?
+        .clone();
+    let column = ID_COLUMNS.iter().find_map(|name| {
+        headers
+            .iter()
+            .position(|header| header.trim().eq_ignore_ascii_case(name))
+    });
+    let column = match column {
+        Some(column) => column,
+        None => return Ok(None),
+    };
+    let mut list = IdList::default();
+    for record in reader.records() {
+        let record = record.map_err(|err| Error::IdList(path.to_path_buf(), err.to_string()))?;
+        let value = record.get(column).unwrap_or("");
+        if value.trim().is_empty() {
+            continue;
+        }
+        match parse_id(value) {
+            Some(id) => list.ids.push(id),
+            None => list.errors.push(Error::MalformedIdList(
+                path.to_path_buf(),
+                record
+                    .position()
+                    .map_or(0, |position| position.line() as usize),
+            )),
+        }
+    }
+    Ok(Some(list))
+}
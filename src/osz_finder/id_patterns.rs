@@ -0,0 +1,85 @@
+use crate::error::Error;
+use regex::Regex;
+use std::path::Path;
+
+// Tried in this order: the first pattern that matches wins, so the explicit
+// forms come before leading digits, which "2 Mello - Title (123456)" also has.
+// A parenthesised id needs three digits or more, so the "(1)" browsers add to
+// "123456 Artist - Title (1)" when it is downloaded again is not taken for one.
+pub const DEFAULT_ID_PATTERNS: [&str; 3] = [
+    r"\((?P<id>\d{3,})\)$",
+    r"(?i)^beatmapset[-_ ]?(?P<id>\d+)",
+    r"^(?P<id>\d+)",
+];
+
+#[derive(Debug, Clone, new)]
+pub struct IdPatterns {
+    patterns: Vec<Regex>,
+}
+
+impl Default for IdPatterns {
+    fn default() -> Self {
+        Self::compile(DEFAULT_ID_PATTERNS.iter()).expect("default id patterns are valid")
+    }
+}
+
+impl IdPatterns {
+    pub fn compile<I, S>(patterns: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut compiled = vec![];
+        for pattern in patterns {
+            let pattern = pattern.as_ref();
+            let regex = Regex::new(pattern)
+                .map_err(|err| Error::InvalidIdPattern(pattern.to_string(), err.to_string()))?;
+            if !regex.capture_names().any(|name| name == Some("id")) {
+                return Err(Error::InvalidIdPattern(
+                    pattern.to_string(),
+                    "it has no capture group named \"id\"".to_string(),
+                ));
+            }
+            compiled.push(regex);
+        }
+        Ok(Self::new(compiled))
+    }
+
+    pub fn beatmapset_id(&self, path: &Path) -> Result<u64, Error> {
+        path.file_stem()
+            .and_then(|osstr| osstr.to_str())
+            .and_then(|stem| {
+                self.patterns.iter().find_map(|pattern| {
+                    pattern
+                        .captures(stem)
+                        .and_then(|captures| captures.name("id"))
+                        .and_then(|id| id.as_str().parse::<u64>().ok())
+                })
+            })
+            .ok_or_else(|| Error::NoBeatmapsetId(path.to_path_buf()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(name: &str) -> Option<u64> {
+        IdPatterns::default()
+            .beatmapset_id(&Path::new("packs").join(name))
+            .ok()
+    }
+
+    #[test]
+    fn parses_default_patterns() {
+        assert_eq!(id("123456 Artist - Title.osz"), Some(123456));
+        assert_eq!(id("123456 Artist - Title (1).osz"), Some(123456));
+        assert_eq!(id("123456n.osz"), Some(123456));
+        assert_eq!(id("beatmapset-123456.osz"), Some(123456));
+        assert_eq!(id("Beatmapset_123456.osz"), Some(123456));
+        assert_eq!(id("Artist - Title (123456).osz"), Some(123456));
+        assert_eq!(id("2 Mello - Title (123456).osz"), Some(123456));
+        assert_eq!(id("Artist - Title (1).osz"), None);
+        assert_eq!(id("Artist - Title.osz"), None);
+    }
+}
@@ -0,0 +1,227 @@
+use super::archive::{self, Archive, ArchiveEntry};
+use crate::error::Error;
+use std::cell::Cell;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, new)]
+pub struct Limits {
+    pub max_entries: usize,
+    pub max_size: u64,
+    pub max_entry_depth: usize,
+    pub timeout: Option<Duration>,
+}
+
+impl Limits {
+    pub fn budget(&self, archive: &Path) -> Budget {
+        Budget::new(self, archive)
+    }
+
+    pub fn list_archive(
+        &self,
+        archive: &Archive,
+        password: Option<&str>,
+    ) -> Result<Vec<ArchiveEntry>, Error> {
+        let budget = self.budget(&archive.path);
+        archive::list_archive(archive, password, &budget).map_err(|err| budget.or(err))
+    }
+
+    pub fn entry_path(&self, archive: &Archive, name: &str) -> Result<PathBuf, Error> {
+        let path = relative_entry_path(name)
+            .ok_or_else(|| Error::UnsafeEntryName(archive.path.clone(), name.to_string()))?;
+        if path.components().count().saturating_sub(1) > self.max_entry_depth {
+            return Err(Error::TooDeep(
+                archive.path.clone(),
+                name.to_string(),
+                self.max_entry_depth,
+            ));
+        }
+        Ok(archive.path.join(path))
+    }
+}
+
+// The entry name as a relative path that cannot leave the folder it is
+// joined to, or None when it is absolute, climbs up or names a drive.
+pub fn relative_entry_path(name: &str) -> Option<PathBuf> {
+    if name.starts_with(['/', '\\']) {
+        return None;
+    }
+    let mut path = PathBuf::new();
+    for segment in name.split(['/', '\\']) {
+        match Path::new(segment).components().next() {
+            None | Some(Component::CurDir) => {}
+            Some(Component::Normal(_)) if !segment.contains(':') => path.push(segment),
+            _ => return None,
+        }
+    }
+    Some(path)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Exceeded {
+    Entries,
+    Size,
+    Time,
+}
+
+// What is left for reading one archive, spent by its backend as it walks the
+// entries and decompresses them.
+#[derive(Debug)]
+pub struct Budget {
+    path: PathBuf,
+    limits: Limits,
+    started: Instant,
+    entries: Cell<usize>,
+    size: Cell<u64>,
+    exceeded: Cell<Option<Exceeded>>,
+}
+
+impl Budget {
+    fn new(limits: &Limits, path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            limits: limits.clone(),
+            started: Instant::now(),
+            entries: Cell::new(0),
+            size: Cell::new(0),
+            exceeded: Cell::new(None),
+        }
+    }
+
+    pub fn entry(&self) -> Result<(), Error> {
+        self.entries.set(self.entries.get() + 1);
+        if self.entries.get() > self.limits.max_entries {
+            return self.exceed(Exceeded::Entries);
+        }
+        self.check_deadline()
+    }
+
+    pub fn consume(&self, bytes: u64) -> Result<(), Error> {
+        self.size.set(self.size.get().saturating_add(bytes));
+        if self.size.get() > self.limits.max_size {
+            return self.exceed(Exceeded::Size);
+        }
+        self.check_deadline()
+    }
+
+    pub fn check_deadline(&self) -> Result<(), Error> {
+        match self.limits.timeout {
+            Some(timeout) if self.started.elapsed() > timeout => self.exceed(Exceeded::Time),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn reader<R: Read>(&self, inner: R) -> BudgetReader<'_, R> {
+        BudgetReader::new(inner, self)
+    }
+
+    // Backends only see the I/O error a spent budget causes, so the limit
+    // that was hit replaces whatever error they report.
+    pub fn or(&self, err: Error) -> Error {
+        match self.exceeded.get() {
+            Some(exceeded) => self.error(exceeded),
+            None => err,
+        }
+    }
+
+    fn exceed(&self, exceeded: Exceeded) -> Result<(), Error> {
+        self.exceeded.set(Some(exceeded));
+        Err(self.error(exceeded))
+    }
+
+    fn error(&self, exceeded: Exceeded) -> Error {
+        match exceeded {
+            Exceeded::Entries => Error::TooManyEntries(self.path.clone(), self.limits.max_entries),
+            Exceeded::Size => Error::TooLarge(self.path.clone(), self.limits.max_size),
+            Exceeded::Time => Error::ArchiveTimeout(
+                self.path.clone(),
+                self.limits.timeout.unwrap_or_default().as_secs(),
+            ),
+        }
+    }
+}
+
+#[derive(new)]
+pub struct BudgetReader<'a, R> {
+    inner: R,
+    budget: &'a Budget,
+}
+
+impl<R: Read> Read for BudgetReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.budget
+            .consume(read as u64)
+            .map_err(|err| std::io::Error::other(err.to_string()))?;
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> Limits {
+        Limits::new(2, 100, 1, None)
+    }
+
+    fn entry_path(name: &str) -> Result<PathBuf, Error> {
+        limits().entry_path(&Archive::single(Path::new("packs/pack.zip")), name)
+    }
+
+    #[test]
+    fn keeps_entries_inside_the_archive() {
+        assert_eq!(
+            entry_path("./a\\b.osz").ok(),
+            Some(Path::new("packs/pack.zip/a/b.osz").into())
+        );
+        for name in [
+            "../x", "a/../b", "a\\..\\b", "/abs", "\\abs", "C:\\x", "a/C:x",
+        ] {
+            assert!(
+                matches!(entry_path(name), Err(Error::UnsafeEntryName(_, _))),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn limits_entry_depth() {
+        assert!(entry_path("a/b.osz").is_ok());
+        assert!(matches!(
+            entry_path("a/b/c.osz"),
+            Err(Error::TooDeep(_, _, 1))
+        ));
+    }
+
+    #[test]
+    fn spends_budget() {
+        let budget = limits().budget(Path::new("packs/pack.zip"));
+        assert!(budget.entry().is_ok());
+        assert!(budget.entry().is_ok());
+        assert!(matches!(budget.entry(), Err(Error::TooManyEntries(_, 2))));
+
+        let budget = limits().budget(Path::new("packs/pack.zip"));
+        assert!(budget.consume(60).is_ok());
+        assert!(matches!(budget.consume(50), Err(Error::TooLarge(_, 100))));
+
+        let budget = limits().budget(Path::new("packs/pack.zip"));
+        let mut content = vec![];
+        let read = budget.reader(&[0u8; 200][..]).read_to_end(&mut content);
+        let err = Error::Unextractable(PathBuf::new(), read.unwrap_err().to_string());
+        assert!(matches!(budget.or(err), Error::TooLarge(_, 100)));
+    }
+
+    #[test]
+    fn spends_time() {
+        let limits = Limits::new(2, 100, 1, Some(Duration::ZERO));
+        let budget = limits.budget(Path::new("packs/pack.zip"));
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(matches!(
+            budget.check_deadline(),
+            Err(Error::ArchiveTimeout(_, 0))
+        ));
+    }
+}
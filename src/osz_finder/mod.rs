@@ -1,54 +1,271 @@
-use std::path::PathBuf;
-
-pub fn find_oszs(path: &PathBuf) -> Vec<PathBuf> {
-    let mut ret = vec![];
-    if path.is_file() || !path.exists() {
-        if let Some(ext) = path
-            .extension()
-            .and_then(|ext| Some(ext.to_str().unwrap().to_string()))
-        {
-            if ext == "osz" {
-                ret.push(path.clone());
-            } else if path.exists() {
-                if let Some(file) = std::fs::File::open(&path).ok() {
-                    if let Some(subfiles) = compress_tools::list_archive_files(file).ok() {
-                        ret.append(
-                            &mut subfiles
-                                .into_iter()
-                                .map(|x: String| {
-                                    path.join(
-                                        x.replace('\\', "/")
-                                            .replace('/', &std::path::MAIN_SEPARATOR.to_string()),
-                                    )
-                                })
-                                .filter(|x| {
-                                    x.extension().and_then(|ext| ext.to_str()).unwrap_or("")
-                                        == "osz"
-                                })
-                                .collect(),
+mod archive;
+mod cache;
+mod id_list;
+mod id_patterns;
+mod limits;
+mod passwords;
+mod pool;
+mod sources;
+mod verify;
+
+use self::archive::ArchiveEntry;
+pub use self::archive::{strip_archive_suffix, Archive};
+pub use self::cache::ArchiveCache;
+pub use self::id_patterns::{IdPatterns, DEFAULT_ID_PATTERNS};
+pub use self::limits::Limits;
+pub use self::passwords::Passwords;
+pub use self::sources::{source_of, PathFilter};
+use crate::error::Error;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, new)]
+pub struct Osz {
+    pub path: PathBuf,
+    pub beatmapset_id: u64,
+    pub size: Option<u64>,
+    pub crc32: Option<u32>,
+    pub archive: Option<(Archive, String)>,
+    pub list: Option<PathBuf>,
+}
+
+impl AsRef<Path> for Osz {
+    fn as_ref(&self) -> &Path {
+        self.path.as_ref()
+    }
+}
+
+#[derive(Debug, Default, new)]
+pub struct OszScan {
+    pub oszs: Vec<Osz>,
+    pub errors: Vec<Error>,
+}
+
+impl OszScan {
+    pub fn append(&mut self, mut other: OszScan) {
+        self.oszs.append(&mut other.oszs);
+        self.errors.append(&mut other.errors);
+    }
+
+    fn push_osz(
+        &mut self,
+        id_patterns: &IdPatterns,
+        path: PathBuf,
+        size: Option<u64>,
+        crc32: Option<u32>,
+        archive: Option<(Archive, String)>,
+    ) {
+        match id_patterns.beatmapset_id(&path) {
+            Ok(beatmapset_id) => {
+                self.oszs
+                    .push(Osz::new(path, beatmapset_id, size, crc32, archive, None))
+            }
+            Err(err) => self.errors.push(err),
+        }
+    }
+
+    fn push_id_list(&mut self, list: &Path, ids: Vec<u64>) {
+        for id in ids {
+            self.oszs.push(Osz::new(
+                list.join(id.to_string()),
+                id,
+                None,
+                None,
+                None,
+                Some(list.to_path_buf()),
+            ));
+        }
+    }
+
+    fn push_listing(
+        &mut self,
+        id_patterns: &IdPatterns,
+        limits: &Limits,
+        archive: &Archive,
+        listing: Result<Vec<ArchiveEntry>, Error>,
+    ) {
+        match listing {
+            Ok(subfiles) => {
+                for subfile in subfiles {
+                    let subpath = match limits.entry_path(archive, &subfile.name) {
+                        Ok(subpath) => subpath,
+                        Err(err) => {
+                            self.errors.push(err);
+                            continue;
+                        }
+                    };
+                    if subpath
+                        .extension()
+                        .and_then(|ext| ext.to_str())
+                        .unwrap_or("")
+                        == "osz"
+                    {
+                        self.push_osz(
+                            id_patterns,
+                            subpath,
+                            subfile.size,
+                            subfile.crc32,
+                            Some((archive.clone(), subfile.name)),
                         );
                     }
                 }
             }
+            Err(err) => {
+                if archive::is_known_archive(&archive.path) {
+                    self.errors.push(err);
+                }
+            }
+        }
+    }
+}
+
+pub fn crc32(reader: &mut dyn Read) -> std::io::Result<(u64, u32)> {
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buffer = [0u8; 64 * 1024];
+    let mut size = 0;
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            return Ok((size, hasher.finalize()));
         }
-    } else if path.is_dir() {
-        for dir_entry in path.read_dir().unwrap() {
-            ret.append(&mut find_oszs(&dir_entry.unwrap().path()));
-        }
-    }
-    ret.into_iter()
-        .filter(|x| {
-            x.file_stem()
-                .and_then(|osstr| osstr.to_str())
-                .and_then(|stem| {
-                    Some(
-                        stem.chars()
-                            .take_while(|c| c.is_numeric())
-                            .collect::<String>(),
-                    )
-                })
-                .and_then(|num| num.parse::<u64>().ok())
-                .is_some()
-        })
-        .collect()
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+}
+
+#[derive(new)]
+pub struct OszFinder {
+    threads: usize,
+    cache: Option<ArchiveCache>,
+    passwords: Passwords,
+    limits: Limits,
+    id_patterns: IdPatterns,
+    filter: PathFilter,
+}
+
+impl OszFinder {
+    pub fn beatmapset_id(&self, path: &Path) -> Result<u64, Error> {
+        self.id_patterns.beatmapset_id(path)
+    }
+
+    // Scans `paths`, which are `root` itself unless they were listed on stdin,
+    // as the packs source `root`.
+    pub fn find_oszs(&mut self, root: &Path, paths: &[PathBuf]) -> OszScan {
+        let mut scan = OszScan::default();
+        let mut archive_paths = vec![];
+        for path in paths {
+            self.find_oszs_(root, path, true, &mut scan, &mut archive_paths);
+        }
+        let (archives, mut volume_errors) = archive::group_volumes(archive_paths);
+        scan.errors.append(&mut volume_errors);
+        let mut listings = vec![];
+        let mut uncached = vec![];
+        for archive in archives {
+            if archive::backend_for(&archive.path).is_none() {
+                scan.errors.push(Error::UnlistableArchive(
+                    archive.path.clone(),
+                    "no backend compiled in".to_string(),
+                ));
+                continue;
+            }
+            match self.cache.as_ref().and_then(|cache| cache.get(&archive)) {
+                Some(listing) => listings.push((archive, listing)),
+                None => uncached.push(archive),
+            }
+        }
+        let (passwords, limits) = (&self.passwords, &self.limits);
+        let listed = pool::map_paths(uncached, self.threads, |archive| {
+            limits.list_archive(archive, passwords.get(&archive.path))
+        });
+        for (archive, listing) in listed {
+            if let Some(cache) = self.cache.as_mut() {
+                if let Err(err) = cache.put(&archive, self.passwords.get(&archive.path), &listing) {
+                    scan.errors.push(err);
+                    self.cache = None;
+                }
+            }
+            listings.push((archive, listing));
+        }
+        if let Some(cache) = self.cache.as_mut() {
+            if let Err(err) = cache.prune(root) {
+                scan.errors.push(err);
+                self.cache = None;
+            }
+        }
+        for (archive, listing) in listings {
+            scan.push_listing(&self.id_patterns, &self.limits, &archive, listing);
+        }
+        let filter = &self.filter;
+        scan.oszs.retain(|osz| filter.matches(root, &osz.path));
+        scan.errors.retain(|err| match err {
+            Error::NoBeatmapsetId(osz_path) => filter.matches(root, osz_path),
+            _ => true,
+        });
+        scan
+    }
+
+    fn find_oszs_(
+        &self,
+        root: &Path,
+        path: &Path,
+        given: bool,
+        scan: &mut OszScan,
+        archives: &mut Vec<PathBuf>,
+    ) {
+        if !given && self.filter.is_excluded(root, path) {
+            return;
+        }
+        if path.is_file() || !path.exists() {
+            if path.to_str().is_none() {
+                // Only the names of files that would have been read matter.
+                if is_candidate(path) {
+                    scan.errors.push(Error::NonUtf8Path(path.to_path_buf()));
+                }
+            } else if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+                if ext == "osz" {
+                    let size = path.metadata().ok().map(|metadata| metadata.len());
+                    scan.push_osz(&self.id_patterns, path.to_path_buf(), size, None, None);
+                } else if id_list::is_id_list(path) {
+                    match id_list::read_id_list(path, given) {
+                        Ok(Some(mut list)) => {
+                            scan.push_id_list(path, list.ids);
+                            scan.errors.append(&mut list.errors);
+                        }
+                        Ok(None) => {}
+                        Err(err) => scan.errors.push(err),
+                    }
+                } else if path.exists()
+                    && (archive::is_known_archive(path)
+                        || archive::is_volume(path)
+                        || archive::backend_for(path).is_some())
+                {
+                    archives.push(path.to_path_buf());
+                }
+            }
+        } else if path.is_dir() {
+            match path.read_dir() {
+                Ok(dir_entries) => {
+                    for dir_entry in dir_entries {
+                        match dir_entry {
+                            Ok(dir_entry) => {
+                                self.find_oszs_(root, &dir_entry.path(), false, scan, archives)
+                            }
+                            Err(err) => scan.errors.push(Error::Io(path.to_path_buf(), err)),
+                        }
+                    }
+                }
+                Err(err) => scan.errors.push(Error::Io(path.to_path_buf(), err)),
+            }
+        }
+    }
+}
+
+// Whether a file is an '.osz', an id list or an archive going by its name,
+// read lossily.
+fn is_candidate(path: &Path) -> bool {
+    let lossy = PathBuf::from(path.to_string_lossy().into_owned());
+    lossy.extension().is_some_and(|ext| ext == "osz")
+        || id_list::is_id_list(&lossy)
+        || archive::is_known_archive(&lossy)
+        || archive::is_volume(&lossy)
 }
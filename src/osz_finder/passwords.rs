@@ -0,0 +1,35 @@
+use crate::error::Error;
+use std::collections::BTreeMap as Map;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default, new)]
+pub struct Passwords {
+    passwords: Map<String, String>,
+}
+
+impl Passwords {
+    pub fn load(path: &PathBuf) -> Result<Self, Error> {
+        let content = std::fs::read_to_string(path).map_err(|err| Error::Io(path.clone(), err))?;
+        let mut passwords = Map::new();
+        for (number, line) in content.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, password) = line
+                .split_once('\t')
+                .ok_or_else(|| Error::MalformedPasswordFile(path.clone(), number + 1))?;
+            passwords.insert(name.trim().to_string(), password.to_string());
+        }
+        Ok(Self::new(passwords))
+    }
+
+    // Volume sets are looked up by their logical name, so pack.part1.rar,
+    // pack.part2.rar, ... use the password given for pack.rar.
+    pub fn get(&self, archive: &Path) -> Option<&str> {
+        archive
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| self.passwords.get(name))
+            .map(|password| password.as_str())
+    }
+}
@@ -0,0 +1,85 @@
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+
+enum Event<I, O> {
+    Started(PathBuf),
+    Finished(usize, I, O),
+}
+
+#[derive(Debug, new)]
+pub struct Progress {
+    enabled: bool,
+    total: usize,
+    #[new(default)]
+    done: usize,
+}
+
+impl Progress {
+    pub fn for_stderr(total: usize) -> Self {
+        Self::new(std::io::stderr().is_terminal(), total)
+    }
+
+    fn show(&self, current: &Path) {
+        if self.enabled {
+            let mut stderr = std::io::stderr();
+            let _ = write!(
+                stderr,
+                "\r\x1b[2K[{}/{}] {}",
+                self.done,
+                self.total,
+                current.display()
+            );
+            let _ = stderr.flush();
+        }
+    }
+
+    fn finish(&self) {
+        if self.enabled {
+            let _ = write!(std::io::stderr(), "\r\x1b[2K");
+        }
+    }
+}
+
+pub fn map_paths<I, O, F>(paths: Vec<I>, threads: usize, work: F) -> Vec<(I, O)>
+where
+    I: AsRef<Path> + Send,
+    O: Send,
+    F: Fn(&I) -> O + Sync,
+{
+    let mut progress = Progress::for_stderr(paths.len());
+    let mut results: Vec<Option<(I, O)>> = paths.iter().map(|_| None).collect();
+    let queue = Arc::new(Mutex::new(paths.into_iter().enumerate()));
+    let (sender, receiver) = mpsc::channel::<Event<I, O>>();
+    std::thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            let queue = queue.clone();
+            let sender = sender.clone();
+            let work = &work;
+            scope.spawn(move || loop {
+                let next = queue.lock().ok().and_then(|mut queue| queue.next());
+                if let Some((seq, path)) = next {
+                    let _ = sender.send(Event::Started(path.as_ref().to_path_buf()));
+                    let output = work(&path);
+                    let _ = sender.send(Event::Finished(seq, path, output));
+                } else {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+        for event in receiver {
+            match event {
+                Event::Started(path) => progress.show(&path),
+                Event::Finished(seq, path, output) => {
+                    progress.done += 1;
+                    progress.show(path.as_ref());
+                    results[seq] = Some((path, output));
+                }
+            }
+        }
+    });
+    progress.finish();
+    results.into_iter().flatten().collect()
+}
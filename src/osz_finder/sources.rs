@@ -0,0 +1,63 @@
+use crate::error::Error;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, new)]
+pub struct PathFilter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl Default for PathFilter {
+    fn default() -> Self {
+        Self::new(None, GlobSet::empty())
+    }
+}
+
+impl PathFilter {
+    pub fn compile(include: &[String], exclude: &[String]) -> Result<Self, Error> {
+        let include = if include.is_empty() {
+            None
+        } else {
+            Some(glob_set(include)?)
+        };
+        Ok(Self::new(include, glob_set(exclude)?))
+    }
+
+    pub fn is_excluded(&self, root: &Path, path: &Path) -> bool {
+        self.exclude.is_match(relative(root, path))
+    }
+
+    pub fn matches(&self, root: &Path, path: &Path) -> bool {
+        let relative = relative(root, path);
+        !self.exclude.is_match(relative)
+            && self
+                .include
+                .as_ref()
+                .map_or(true, |include| include.is_match(relative))
+    }
+}
+
+fn glob_set(patterns: &[String]) -> Result<GlobSet, Error> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(
+            Glob::new(pattern)
+                .map_err(|err| Error::InvalidGlob(pattern.clone(), err.kind().to_string()))?,
+        );
+    }
+    builder
+        .build()
+        .map_err(|err| Error::InvalidGlob(patterns.join(" "), err.kind().to_string()))
+}
+
+fn relative<'a>(root: &Path, path: &'a Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
+
+pub fn source_of<'a>(sources: &'a [PathBuf], path: &Path) -> Option<&'a PathBuf> {
+    sources
+        .iter()
+        .filter(|source| path.starts_with(source))
+        .max_by_key(|source| source.components().count())
+}
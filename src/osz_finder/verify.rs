@@ -0,0 +1,179 @@
+use super::{archive, pool, Limits, Osz, OszFinder, Passwords};
+use crate::error::Error;
+use std::collections::BTreeMap as Map;
+use std::collections::BTreeSet as Set;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+
+// The `.osz` files read in one pass: a loose one, or every requested one
+// inside the same archive.
+struct Job {
+    path: PathBuf,
+    oszs: Vec<(usize, Osz)>,
+}
+
+impl Job {
+    fn new(path: PathBuf) -> Self {
+        Self { path, oszs: vec![] }
+    }
+}
+
+impl AsRef<Path> for Job {
+    fn as_ref(&self) -> &Path {
+        self.path.as_ref()
+    }
+}
+
+impl OszFinder {
+    pub fn verify(&self, oszs: &[Osz]) -> Vec<(Osz, Result<(), Error>)> {
+        self.map_contents(oszs, verify)
+    }
+
+    pub fn map_contents<O, F>(&self, oszs: &[Osz], work: F) -> Vec<(Osz, Result<O, Error>)>
+    where
+        O: Send,
+        F: Fn(&Osz, &mut dyn Read) -> Result<O, Error> + Sync,
+    {
+        let mut jobs: Vec<Job> = vec![];
+        let mut archives = Map::new();
+        for (index, osz) in oszs.iter().enumerate() {
+            if osz.list.is_some() {
+                continue;
+            }
+            let job = match &osz.archive {
+                Some((archive, _)) => *archives.entry(archive.path.clone()).or_insert_with(|| {
+                    jobs.push(Job::new(archive.path.clone()));
+                    jobs.len() - 1
+                }),
+                None => {
+                    jobs.push(Job::new(osz.path.clone()));
+                    jobs.len() - 1
+                }
+            };
+            jobs[job].oszs.push((index, osz.clone()));
+        }
+        let (passwords, limits) = (&self.passwords, &self.limits);
+        let mut results: Vec<(usize, Osz, Result<O, Error>)> =
+            pool::map_paths(jobs, self.threads, |job| {
+                let oszs: Vec<&Osz> = job.oszs.iter().map(|(_, osz)| osz).collect();
+                read_oszs(passwords, limits, &oszs, &mut |osz, reader| {
+                    work(osz, reader)
+                })
+            })
+            .into_iter()
+            .flat_map(|(job, results)| {
+                job.oszs
+                    .into_iter()
+                    .zip(results)
+                    .map(|((index, osz), result)| (index, osz, result))
+            })
+            .collect();
+        results.sort_by_key(|(index, _, _)| *index);
+        results
+            .into_iter()
+            .map(|(_, osz, result)| (osz, result))
+            .collect()
+    }
+}
+
+// Reads `oszs`, which all come from the same archive unless there is a
+// single loose one, handing each one's content to `work`.
+fn read_oszs<O>(
+    passwords: &Passwords,
+    limits: &Limits,
+    oszs: &[&Osz],
+    work: &mut dyn FnMut(&Osz, &mut dyn Read) -> Result<O, Error>,
+) -> Vec<Result<O, Error>> {
+    let archive = match oszs.first().and_then(|osz| osz.archive.as_ref()) {
+        Some((archive, _)) => archive,
+        None => {
+            return oszs
+                .iter()
+                .map(|osz| {
+                    let mut file = std::fs::File::open(&osz.path)
+                        .map_err(|err| Error::Io(osz.path.clone(), err))?;
+                    work(osz, &mut file)
+                })
+                .collect()
+        }
+    };
+    let names: Set<String> = oszs.iter().filter_map(|osz| entry_name(osz)).collect();
+    let mut results: Vec<Option<Result<O, Error>>> = oszs.iter().map(|_| None).collect();
+    let budget = limits.budget(&archive.path);
+    let extracted = archive::extract_entries(
+        archive,
+        passwords.get(&archive.path),
+        &names,
+        &budget,
+        &mut |name, reader| {
+            let index = oszs
+                .iter()
+                .position(|osz| entry_name(osz).as_deref() == Some(name));
+            if let Some(index) = index {
+                let osz = oszs[index];
+                results[index] = Some(
+                    reader
+                        .and_then(|reader| work(osz, reader))
+                        .map_err(|err| budget.or(err)),
+                );
+            }
+        },
+    )
+    .map_err(|err| budget.or(err));
+    oszs.iter()
+        .zip(results)
+        .map(|(osz, result)| match (result, &extracted) {
+            (Some(result), _) => result,
+            (None, Err(err)) => Err(archive_error(osz, err)),
+            (None, Ok(())) => Err(Error::MissingEntry(
+                archive.path.clone(),
+                entry_name(osz).unwrap_or_default(),
+            )),
+        })
+        .collect()
+}
+
+fn entry_name(osz: &Osz) -> Option<String> {
+    osz.archive.as_ref().map(|(_, name)| name.clone())
+}
+
+// The error that stopped reading an archive, repeated for each `.osz` that
+// was not reached.
+fn archive_error(osz: &Osz, err: &Error) -> Error {
+    match err {
+        Error::EncryptedArchive(path) => Error::EncryptedArchive(path.clone()),
+        Error::WrongPassword(path) => Error::WrongPassword(path.clone()),
+        Error::TooManyEntries(path, max) => Error::TooManyEntries(path.clone(), *max),
+        Error::TooLarge(path, max) => Error::TooLarge(path.clone(), *max),
+        Error::ArchiveTimeout(path, timeout) => Error::ArchiveTimeout(path.clone(), *timeout),
+        err => Error::Unextractable(osz.path.clone(), err.to_string()),
+    }
+}
+
+fn verify(osz: &Osz, reader: &mut dyn Read) -> Result<(), Error> {
+    let mut content = vec![];
+    reader
+        .read_to_end(&mut content)
+        .map_err(|err| Error::Unextractable(osz.path.clone(), err.to_string()))?;
+    let size = content.len() as u64;
+    if let Some(expected) = osz.size.filter(|expected| *expected != size) {
+        return Err(Error::TruncatedOsz(osz.path.clone(), expected, size));
+    }
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(&content);
+    let crc32 = hasher.finalize();
+    if let Some(expected) = osz.crc32.filter(|expected| *expected != crc32) {
+        return Err(Error::ChecksumMismatch(osz.path.clone(), expected, crc32));
+    }
+    let corrupt = |err: String| Error::CorruptOsz(osz.path.clone(), err);
+    let mut zip =
+        zip::ZipArchive::new(Cursor::new(content)).map_err(|err| corrupt(err.to_string()))?;
+    for index in 0..zip.len() {
+        let mut file = zip
+            .by_index(index)
+            .map_err(|err| corrupt(err.to_string()))?;
+        std::io::copy(&mut file, &mut std::io::sink())
+            .map_err(|err| corrupt(format!("{} {}", file.name(), err)))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use super::{Pack, PackSeries};
+use crate::error::Error;
+use serde::Deserialize;
+use std::collections::BTreeMap as Map;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Deserialize, new)]
+pub struct CatalogueBeatmapset {
+    pub id: u64,
+    #[serde(default)]
+    pub artist: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+impl std::fmt::Display for CatalogueBeatmapset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.id)?;
+        match (&self.artist, &self.title) {
+            (Some(artist), Some(title)) => write!(f, " {} - {}", artist, title),
+            (Some(name), None) | (None, Some(name)) => write!(f, " {}", name),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, new)]
+pub struct CataloguePack {
+    pub pack: Pack,
+    pub beatmapsets: Vec<CatalogueBeatmapset>,
+}
+
+#[derive(Deserialize)]
+struct JsonPack {
+    pack: String,
+    #[serde(default)]
+    title: Option<String>,
+    beatmapsets: Vec<CatalogueBeatmapset>,
+}
+
+#[derive(Deserialize)]
+struct CsvRow {
+    pack: String,
+    #[serde(default)]
+    pack_title: Option<String>,
+    beatmapset_id: u64,
+    #[serde(default)]
+    artist: Option<String>,
+    #[serde(default)]
+    title: Option<String>,
+}
+
+#[derive(Debug, Default, new)]
+pub struct Catalogue {
+    pub packs: Map<(PackSeries, u64), CataloguePack>,
+}
+
+impl Catalogue {
+    pub fn load(path: &PathBuf) -> Result<Self, Error> {
+        let error = |err: String| Error::Catalogue(path.clone(), err);
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        let mut catalogue = Self::default();
+        if extension == "json" {
+            let file = std::fs::File::open(path).map_err(|err| Error::Io(path.clone(), err))?;
+            let packs: Vec<JsonPack> = serde_json::from_reader(std::io::BufReader::new(file))
+                .map_err(|err| error(err.to_string()))?;
+            for json_pack in packs {
+                catalogue
+                    .pack(path, &json_pack.pack, json_pack.title)?
+                    .beatmapsets
+                    .extend(json_pack.beatmapsets);
+            }
+        } else if extension == "csv" {
+            let mut reader = csv::Reader::from_path(path).map_err(|err| error(err.to_string()))?;
+            for row in reader.deserialize() {
+                let row: CsvRow = row.map_err(|err| error(err.to_string()))?;
+                catalogue
+                    .pack(path, &row.pack, row.pack_title)?
+                    .beatmapsets
+                    .push(CatalogueBeatmapset::new(
+                        row.beatmapset_id,
+                        row.artist,
+                        row.title,
+                    ));
+            }
+        } else {
+            return Err(error(
+                "only .json and .csv catalogues are supported".to_string(),
+            ));
+        }
+        Ok(catalogue)
+    }
+
+    fn pack(
+        &mut self,
+        path: &Path,
+        id: &str,
+        title: Option<String>,
+    ) -> Result<&mut CataloguePack, Error> {
+        let mut pack = Pack::parse(id).ok_or_else(|| {
+            Error::Catalogue(path.to_path_buf(), format!("{:?} is not a pack id", id))
+        })?;
+        if title.is_some() {
+            pack.title = title;
+        }
+        let catalogue_pack = self
+            .packs
+            .entry((pack.series, pack.number))
+            .or_insert_with(|| CataloguePack::new(pack.clone(), vec![]));
+        if catalogue_pack.pack.title.is_none() {
+            catalogue_pack.pack.title = pack.title;
+        }
+        Ok(catalogue_pack)
+    }
+}
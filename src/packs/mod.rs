@@ -0,0 +1,118 @@
+mod catalogue;
+
+pub use self::catalogue::Catalogue;
+use crate::osu::OsuMode;
+use crate::osz_finder::strip_archive_suffix;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+pub enum PackSeries {
+    #[display(fmt = "osu!standard")]
+    Standard,
+    #[display(fmt = "osu!taiko")]
+    Taiko,
+    #[display(fmt = "osu!catch")]
+    Catch,
+    #[display(fmt = "osu!mania")]
+    Mania,
+    #[display(fmt = "Artist/Album")]
+    ArtistAlbum,
+    #[display(fmt = "Theme")]
+    Theme,
+}
+
+impl PackSeries {
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            PackSeries::Standard => "S",
+            PackSeries::Taiko => "T",
+            PackSeries::Catch => "C",
+            PackSeries::Mania => "M",
+            PackSeries::ArtistAlbum => "SA",
+            PackSeries::Theme => "ST",
+        }
+    }
+
+    pub fn mode(&self) -> Option<OsuMode> {
+        match self {
+            PackSeries::Standard => Some(OsuMode::Standard),
+            PackSeries::Taiko => Some(OsuMode::Taiko),
+            PackSeries::Catch => Some(OsuMode::Catch),
+            PackSeries::Mania => Some(OsuMode::Mania),
+            PackSeries::ArtistAlbum | PackSeries::Theme => None,
+        }
+    }
+
+    fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix {
+            "S" => Some(PackSeries::Standard),
+            "T" => Some(PackSeries::Taiko),
+            "C" => Some(PackSeries::Catch),
+            "M" => Some(PackSeries::Mania),
+            "SA" => Some(PackSeries::ArtistAlbum),
+            "ST" => Some(PackSeries::Theme),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, new)]
+pub struct Pack {
+    pub series: PackSeries,
+    pub number: u64,
+    pub title: Option<String>,
+}
+
+impl Pack {
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = strip_archive_suffix(name);
+        let prefix: String = name
+            .chars()
+            .take_while(|c| c.is_ascii_uppercase())
+            .collect();
+        let series = PackSeries::from_prefix(&prefix)?;
+        let rest = &name[prefix.len()..];
+        let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+        let number = digits.parse().ok()?;
+        let rest = &rest[digits.len()..];
+        if rest.chars().next().is_some_and(|c| c.is_alphanumeric()) {
+            return None;
+        }
+        let title = rest.trim_start_matches([' ', '-', '_']);
+        Some(Self::new(
+            series,
+            number,
+            Some(title.trim().to_string()).filter(|title| !title.is_empty()),
+        ))
+    }
+
+    pub fn find(osz: &Path) -> Option<Self> {
+        let packs: Vec<Self> = osz
+            .parent()?
+            .iter()
+            .filter_map(|segment| segment.to_str())
+            .filter_map(Self::parse)
+            .collect();
+        let mut pack = packs.last()?.clone();
+        if pack.title.is_none() {
+            pack.title = packs
+                .iter()
+                .filter(|x| x.series == pack.series && x.number == pack.number)
+                .find_map(|x| x.title.clone());
+        }
+        Some(pack)
+    }
+
+    pub fn code(&self) -> String {
+        format!("{}{}", self.series.prefix(), self.number)
+    }
+}
+
+impl std::fmt::Display for Pack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.title {
+            Some(title) => write!(f, "{} - {}", self.code(), title),
+            None => write!(f, "{}", self.code()),
+        }
+    }
+}
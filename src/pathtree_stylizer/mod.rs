@@ -1,4 +1,7 @@
+use crate::error::Error;
+use crate::osz_finder::source_of;
 use std::collections::BTreeMap as Map;
+use std::convert::TryFrom;
 use std::path::PathBuf;
 
 #[derive(Debug, PartialEq, Eq, Clone, new)]
@@ -17,6 +20,15 @@ impl<T> PathTreeStylized<T>
 where
     T: Clone + PartialEq + Eq + PartialOrd + Ord + std::fmt::Debug,
 {
+    pub fn segment(&self) -> &str {
+        &self.segment
+    }
+    pub fn data(&self) -> Option<&T> {
+        self.data.as_ref()
+    }
+    pub fn children(&self) -> &[PathTreeStylized<T>] {
+        &self.children
+    }
     fn get_child_mut(&mut self, segment: &String) -> Option<&mut PathTreeStylized<T>> {
         self.children
             .iter_mut()
@@ -136,19 +148,33 @@ where
     }
 }
 
-impl<T> From<&Vec<(PathBuf, T)>> for PathTreeStylized<T>
+impl<T> TryFrom<(&[PathBuf], &Vec<(PathBuf, T)>)> for PathTreeStylized<T>
 where
     T: Clone + PartialEq + Eq + PartialOrd + Ord + std::fmt::Debug,
 {
-    fn from(v: &Vec<(PathBuf, T)>) -> Self {
+    type Error = Error;
+    fn try_from((roots, v): (&[PathBuf], &Vec<(PathBuf, T)>)) -> Result<Self, Self::Error> {
         let mut root_tree = Self::new("".to_string(), None, None, None, vec![]);
         for (path, data) in v.iter() {
-            root_tree.add_child(
-                &mut path.iter().map(|x| x.to_str().unwrap().to_string()),
-                (*data).clone(),
-            );
+            let (root, relative) = match source_of(roots, path) {
+                Some(root) => (
+                    Some(
+                        root.to_str()
+                            .ok_or_else(|| Error::NonUtf8Path(root.clone()))?,
+                    ),
+                    path.strip_prefix(root).unwrap_or(path),
+                ),
+                None => (None, path.as_path()),
+            };
+            let segments = root
+                .map(|root| Some(root.to_string()))
+                .into_iter()
+                .chain(relative.iter().map(|x| x.to_str().map(|x| x.to_string())))
+                .collect::<Option<Vec<String>>>()
+                .ok_or_else(|| Error::NonUtf8Path(path.clone()))?;
+            root_tree.add_child(&mut segments.into_iter(), (*data).clone());
         }
-        root_tree
+        Ok(root_tree)
     }
 }
 
@@ -0,0 +1,181 @@
+use crate::error::Error;
+use crate::install::copy_osz;
+use crate::osu::{Osu, OsuMode};
+use crate::osz_finder::{source_of, strip_archive_suffix, Osz, OszFinder};
+use crate::packs::Pack;
+use std::collections::BTreeSet as Set;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, new)]
+pub struct StagingFilter {
+    packs: Vec<String>,
+    modes: Vec<OsuMode>,
+    min_stars: Option<f64>,
+    max_stars: Option<f64>,
+}
+
+impl StagingFilter {
+    pub fn matches(&self, osu: &dyn Osu, osz: &Osz) -> bool {
+        let pack = Pack::find(&osz.path);
+        if !self.packs.is_empty()
+            && !pack.as_ref().is_some_and(|pack| {
+                self.packs.iter().any(|wanted| {
+                    wanted.eq_ignore_ascii_case(&pack.code())
+                        || wanted.eq_ignore_ascii_case(pack.series.prefix())
+                })
+            })
+        {
+            return false;
+        }
+        if self.modes.is_empty() && self.min_stars.is_none() && self.max_stars.is_none() {
+            return true;
+        }
+        let mut difficulties: Vec<(Option<OsuMode>, Option<f64>)> = osu
+            .get_beatmapset_maps(osz.beatmapset_id)
+            .iter()
+            .map(|beatmap| (beatmap.mode(), beatmap.stars()))
+            .collect();
+        if difficulties.is_empty() {
+            difficulties.push((pack.and_then(|pack| pack.series.mode()), None));
+        }
+        difficulties.into_iter().any(|(mode, stars)| {
+            (self.modes.is_empty() || mode.is_some_and(|mode| self.modes.contains(&mode)))
+                && self
+                    .min_stars
+                    .map_or(true, |min| stars.is_some_and(|x| x >= min))
+                && self
+                    .max_stars
+                    .map_or(true, |max| stars.is_some_and(|x| x <= max))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum StagingAction {
+    #[display(fmt = "copied")]
+    Copied,
+    #[display(fmt = "linked")]
+    Linked,
+    #[display(fmt = "would be staged")]
+    WouldStage,
+    #[display(fmt = "already staged")]
+    AlreadyStaged,
+}
+
+#[derive(Debug, new)]
+pub struct Staging {
+    pub osz: Osz,
+    pub destination: PathBuf,
+    pub action: Result<StagingAction, Error>,
+}
+
+#[derive(Debug, Clone, new)]
+pub struct Stager {
+    packs_sources: Vec<PathBuf>,
+    target: PathBuf,
+    hard_link: bool,
+    dry_run: bool,
+    limit: Option<usize>,
+}
+
+impl Stager {
+    pub fn stage(&self, finder: &OszFinder, oszs: &[&Osz]) -> Vec<Staging> {
+        let mut stagings: Vec<Staging> = vec![];
+        let mut staged = 0;
+        let mut next = 0;
+        while next < oszs.len() && self.below_limit(staged) {
+            // Copies out of archives are batched so that each archive is read
+            // once per batch; a batch stops before a destination it already
+            // holds, which is only staged if the earlier copy fails.
+            let mut batch: Vec<usize> = vec![];
+            let mut destinations = Set::new();
+            while next < oszs.len()
+                && self.below_limit(staged + batch.len())
+                && !destinations.contains(&self.destination(oszs[next]))
+            {
+                let osz = oszs[next];
+                next += 1;
+                let destination = self.destination(osz);
+                let action = if destination.exists() {
+                    Ok(StagingAction::AlreadyStaged)
+                } else if self.dry_run {
+                    staged += 1;
+                    Ok(StagingAction::WouldStage)
+                } else {
+                    match self.link(osz, &destination) {
+                        Ok(true) => {
+                            staged += 1;
+                            Ok(StagingAction::Linked)
+                        }
+                        Ok(false) => {
+                            batch.push(stagings.len());
+                            destinations.insert(destination.clone());
+                            Ok(StagingAction::Copied)
+                        }
+                        Err(err) => Err(err),
+                    }
+                };
+                stagings.push(Staging::new((*osz).clone(), destination, action));
+            }
+            let batch_oszs: Vec<Osz> = batch
+                .iter()
+                .map(|index| stagings[*index].osz.clone())
+                .collect();
+            let copied = finder.map_contents(&batch_oszs, |osz, reader| {
+                copy_osz(osz, reader, &self.destination(osz))
+            });
+            for (index, (_, copied)) in batch.into_iter().zip(copied) {
+                match copied {
+                    Ok(()) => staged += 1,
+                    Err(err) => stagings[index].action = Err(err),
+                }
+            }
+        }
+        stagings
+    }
+
+    fn below_limit(&self, staged: usize) -> bool {
+        match self.limit {
+            Some(limit) => staged < limit,
+            None => true,
+        }
+    }
+
+    // Archives become directories named after them without their suffix, so
+    // `S1.zip/S1/1.osz` is staged as `S1/S1/1.osz`.
+    fn destination(&self, osz: &Osz) -> PathBuf {
+        let packs_source = match source_of(&self.packs_sources, &osz.path) {
+            Some(packs_source) => packs_source,
+            None => return self.target.join(osz.path.file_name().unwrap_or_default()),
+        };
+        let root = match packs_source.parent() {
+            Some(parent) if packs_source.is_file() || self.packs_sources.len() > 1 => parent,
+            _ => packs_source.as_path(),
+        };
+        let relative = match osz.path.strip_prefix(root) {
+            Ok(relative) => relative,
+            Err(_) => return self.target.join(osz.path.file_name().unwrap_or_default()),
+        };
+        let mut destination = self.target.clone();
+        let mut components = relative.iter().peekable();
+        while let Some(component) = components.next() {
+            if components.peek().is_none() {
+                destination.push(component);
+            } else {
+                destination.push(strip_archive_suffix(&component.to_string_lossy()));
+            }
+        }
+        destination
+    }
+
+    // Creates the destination's directory and, for a loose `.osz` with
+    // --hard-link, links it there. Returns whether it was linked.
+    fn link(&self, osz: &Osz, destination: &Path) -> Result<bool, Error> {
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent).map_err(|err| Error::Io(parent.to_path_buf(), err))?;
+        }
+        Ok(self.hard_link
+            && osz.archive.is_none()
+            && std::fs::hard_link(&osz.path, destination).is_ok())
+    }
+}
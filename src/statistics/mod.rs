@@ -0,0 +1,53 @@
+use crate::osu::OsuBeatmapStatus;
+use std::collections::BTreeSet as Set;
+
+#[derive(Debug, Default, Clone, Display, new)]
+#[display(
+    fmt = "{}/{} beatmapsets played ({:.1}%), {} not played, {} not installed",
+    played,
+    "self.total()",
+    "self.percentage()",
+    not_played,
+    not_installed
+)]
+pub struct Statistics {
+    pub played: usize,
+    pub not_played: usize,
+    pub not_installed: usize,
+}
+
+impl Statistics {
+    pub fn from_statuses<'a, I>(statuses: I, count_once: bool) -> Self
+    where
+        I: Iterator<Item = (u64, &'a OsuBeatmapStatus)>,
+    {
+        let mut statistics = Self::default();
+        let mut counted = Set::new();
+        for (beatmapset_id, status) in statuses {
+            if !count_once || counted.insert(beatmapset_id) {
+                statistics.add(status);
+            }
+        }
+        statistics
+    }
+
+    pub fn add(&mut self, status: &OsuBeatmapStatus) {
+        match status {
+            OsuBeatmapStatus::Played(_) => self.played += 1,
+            OsuBeatmapStatus::NotPlayed => self.not_played += 1,
+            OsuBeatmapStatus::NotInstalled => self.not_installed += 1,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.played + self.not_played + self.not_installed
+    }
+
+    pub fn percentage(&self) -> f64 {
+        if self.total() == 0 {
+            0.0
+        } else {
+            100.0 * self.played as f64 / self.total() as f64
+        }
+    }
+}
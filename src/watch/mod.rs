@@ -0,0 +1,67 @@
+use crate::error::Error;
+use notify::{DebouncedEvent, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+const OSU_DATABASES: [&str; 4] = ["osu!.db", "scores.db", "collection.db", "client.db"];
+
+pub fn watch<F>(
+    osu_source: &PathBuf,
+    packs_sources: &[PathBuf],
+    debounce: Duration,
+    mut render: F,
+) -> Result<(), Error>
+where
+    F: FnMut(),
+{
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::watcher(sender, debounce)
+        .map_err(|err| Error::Watch(osu_source.clone(), err.to_string()))?;
+    watcher
+        .watch(osu_source, RecursiveMode::NonRecursive)
+        .map_err(|err| Error::Watch(osu_source.clone(), err.to_string()))?;
+    for packs_source in packs_sources {
+        watcher
+            .watch(packs_source, RecursiveMode::Recursive)
+            .map_err(|err| Error::Watch(packs_source.clone(), err.to_string()))?;
+    }
+    let osu_source = osu_source.canonicalize().unwrap_or(osu_source.clone());
+    render();
+    loop {
+        let event = receiver
+            .recv()
+            .map_err(|err| Error::Watch(osu_source.clone(), err.to_string()))?;
+        if is_relevant(&osu_source, &event) {
+            while receiver.try_recv().is_ok() {}
+            render();
+        }
+    }
+}
+
+fn is_relevant(osu_source: &Path, event: &DebouncedEvent) -> bool {
+    let path = match event {
+        DebouncedEvent::Create(path)
+        | DebouncedEvent::Write(path)
+        | DebouncedEvent::Remove(path)
+        | DebouncedEvent::Rename(_, path) => path,
+        DebouncedEvent::Rescan => return true,
+        _ => return false,
+    };
+    // notify reports paths as they were watched, so a symlinked osu! folder
+    // only matches once both sides are canonical.
+    let in_osu_source = path.parent().is_some_and(|parent| {
+        parent
+            .canonicalize()
+            .unwrap_or_else(|_| parent.to_path_buf())
+            == osu_source
+    });
+    if in_osu_source {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .map(|name| OSU_DATABASES.contains(&name))
+            .unwrap_or(false)
+    } else {
+        true
+    }
+}